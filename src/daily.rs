@@ -0,0 +1,398 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+use bevy::prelude::*;
+use chrono::{Datelike, Local, NaiveDate};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    AdaptiveDifficulty, Coop, Difficulty, FinalScore, GameMode, Mutator, Mutators, RunSeed,
+    StartingBonuses,
+};
+use crate::meta::MetaSave;
+use crate::AppState;
+
+/// Run shared by every player on the same local date, with a separate score
+/// history. The first run of the day is the official attempt, the others are
+/// practice.
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<StartDailyChallenge>()
+            .add_systems(Startup, load_daily_history)
+            .add_systems(Update, start_daily_challenge)
+            .add_systems(OnEnter(AppState::Game), begin_daily_attempt)
+            .add_systems(OnEnter(AppState::GameOver), record_daily_score)
+            .add_systems(OnEnter(AppState::GameWon), record_daily_score)
+            .add_systems(OnEnter(AppState::MainMenu), end_daily_challenge);
+    }
+}
+
+/// Sent from the main menu to play today's [DailyChallenge]
+#[derive(Event, Clone, Copy)]
+pub struct StartDailyChallenge;
+
+/// Settings of the run of a given date, all derived from its seed
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyChallenge {
+    pub date: NaiveDate,
+    pub seed: u64,
+    pub mode: GameMode,
+    pub mutators: Mutators,
+}
+
+impl DailyChallenge {
+    /// Number of [Mutator]s active in every challenge
+    pub const MUTATORS: usize = 2;
+
+    pub fn today() -> Self {
+        Self::for_date(Local::now().date_naive())
+    }
+
+    pub fn for_date(date: NaiveDate) -> Self {
+        let seed = date.year() as u64 * 10_000 + date.month() as u64 * 100 + date.day() as u64;
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let mode = *GameMode::SOLO
+            .choose(&mut rng)
+            .unwrap_or(&GameMode::Endless);
+        let mut mutators = Mutators::default();
+        for &mutator in Mutator::ALL.choose_multiple(&mut rng, Self::MUTATORS) {
+            mutators.toggle(mutator);
+        }
+
+        Self {
+            date,
+            seed,
+            mode,
+            mutators,
+        }
+    }
+
+    /// Key of the date in the [DailyHistory]
+    pub fn key(&self) -> String {
+        self.date.format("%Y-%m-%d").to_string()
+    }
+}
+
+/// Scores of a single day
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct DailyRecord {
+    /// The official attempt was started, even if it was never finished
+    pub official_used: bool,
+    pub official_score: Option<u32>,
+    /// Best first
+    pub practice_scores: Vec<u32>,
+}
+
+impl DailyRecord {
+    pub const PRACTICE_KEPT: usize = 10;
+
+    pub fn best_practice(&self) -> Option<u32> {
+        self.practice_scores.first().copied()
+    }
+}
+
+/// Local [DailyRecord] of every played date, saved in [DailyHistory::PATH]
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DailyHistory {
+    pub days: BTreeMap<String, DailyRecord>,
+}
+
+impl DailyHistory {
+    pub const PATH: &'static str = "daily.json";
+
+    pub fn load() -> io::Result<Self> {
+        let file = match fs::read_to_string(Self::PATH) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err),
+        };
+
+        serde_json::from_str(&file).map_err(io::Error::from)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let file = serde_json::to_string_pretty(self)?;
+        fs::write(Self::PATH, file)
+    }
+
+    pub fn day(&self, challenge: &DailyChallenge) -> DailyRecord {
+        self.days.get(&challenge.key()).cloned().unwrap_or_default()
+    }
+
+    pub fn official_available(&self, challenge: &DailyChallenge) -> bool {
+        !self.day(challenge).official_used
+    }
+
+    pub fn record(&mut self, challenge: &DailyChallenge, official: bool, score: u32) {
+        let day = self.days.entry(challenge.key()).or_default();
+        if official {
+            day.official_score = Some(score);
+            return;
+        }
+
+        let index = day.practice_scores.partition_point(|&s| s >= score);
+        day.practice_scores.insert(index, score);
+        day.practice_scores.truncate(DailyRecord::PRACTICE_KEPT);
+    }
+}
+
+/// Menu settings replaced by the [DailyChallenge], given back when leaving it
+#[derive(Debug, Clone)]
+struct PreviousSettings {
+    mode: GameMode,
+    mutators: Mutators,
+    difficulty: Difficulty,
+    adaptive: bool,
+    coop: Coop,
+    seed: RunSeed,
+}
+
+/// Present while the daily challenge is played, including its retries
+#[derive(Resource, Debug, Clone)]
+pub struct DailyRun {
+    pub challenge: DailyChallenge,
+    /// The current attempt is the official one of the day
+    pub official: bool,
+    previous: PreviousSettings,
+}
+
+impl DailyRun {
+    /// Lines shown on the end screen
+    pub fn summary(&self, history: &DailyHistory) -> Vec<String> {
+        let day = history.day(&self.challenge);
+        let attempt = if self.official {
+            "official"
+        } else {
+            "practice"
+        };
+
+        let mut lines = vec![format!(
+            "Daily challenge {} ({attempt})",
+            self.challenge.key()
+        )];
+        match day.official_score {
+            Some(score) => lines.push(format!("Official score: {score}")),
+            None => lines.push("Official score: -".to_string()),
+        }
+        if let Some(best) = day.best_practice() {
+            lines.push(format!("Best practice score: {best}"));
+        }
+
+        lines
+    }
+}
+
+/// Label of the main menu entry
+pub fn daily_label(history: &DailyHistory) -> String {
+    let challenge = DailyChallenge::today();
+    let attempt = if history.official_available(&challenge) {
+        "official attempt"
+    } else {
+        "practice"
+    };
+
+    format!(
+        "Daily challenge: {}, {} mutators ({attempt}).",
+        challenge.mode.name(),
+        challenge.mutators.len()
+    )
+}
+
+fn save_history(history: &DailyHistory) {
+    if let Err(err) = history.save() {
+        warn!("Could not save {}: {err}", DailyHistory::PATH);
+    }
+}
+
+fn load_daily_history(mut commands: Commands) {
+    let history = DailyHistory::load().unwrap_or_else(|err| {
+        warn!("Could not load {}: {err}", DailyHistory::PATH);
+        DailyHistory::default()
+    });
+
+    commands.insert_resource(history);
+}
+
+/// Every player gets the same settings: no co-op and no [StartingBonuses]
+#[allow(clippy::too_many_arguments)]
+fn start_daily_challenge(
+    mut commands: Commands,
+    mut start: EventReader<StartDailyChallenge>,
+    mut mode: ResMut<GameMode>,
+    mut mutators: ResMut<Mutators>,
+    mut difficulty: ResMut<Difficulty>,
+    mut adaptive: ResMut<AdaptiveDifficulty>,
+    mut coop: ResMut<Coop>,
+    mut bonuses: ResMut<StartingBonuses>,
+    mut seed: ResMut<RunSeed>,
+    mut state: ResMut<NextState<AppState>>,
+) {
+    if start.read().count() == 0 {
+        return;
+    }
+
+    let challenge = DailyChallenge::today();
+    let previous = PreviousSettings {
+        mode: *mode,
+        mutators: mutators.clone(),
+        difficulty: *difficulty,
+        adaptive: adaptive.enabled,
+        coop: *coop,
+        seed: *seed,
+    };
+
+    *mode = challenge.mode;
+    *mutators = challenge.mutators.clone();
+    *difficulty = Difficulty::Normal;
+    adaptive.enabled = false;
+    coop.enabled = false;
+    *bonuses = StartingBonuses::default();
+    **seed = Some(challenge.seed);
+
+    commands.insert_resource(DailyRun {
+        challenge,
+        official: false,
+        previous,
+    });
+    state.set(AppState::Game);
+}
+
+/// Every run started during the challenge uses the official attempt if it is
+/// still available
+fn begin_daily_attempt(mut daily: Option<ResMut<DailyRun>>, mut history: ResMut<DailyHistory>) {
+    let Some(daily) = &mut daily else {
+        return;
+    };
+
+    daily.official = history.official_available(&daily.challenge);
+    if daily.official {
+        history
+            .days
+            .entry(daily.challenge.key())
+            .or_default()
+            .official_used = true;
+        save_history(&history);
+    }
+}
+
+pub fn record_daily_score(
+    daily: Option<Res<DailyRun>>,
+    final_score: Option<Res<FinalScore>>,
+    mut history: ResMut<DailyHistory>,
+) {
+    let (Some(daily), Some(final_score)) = (daily, final_score) else {
+        return;
+    };
+
+    history.record(&daily.challenge, daily.official, final_score.score);
+    save_history(&history);
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn end_daily_challenge(
+    mut commands: Commands,
+    daily: Option<Res<DailyRun>>,
+    meta: Res<MetaSave>,
+    mut mode: ResMut<GameMode>,
+    mut mutators: ResMut<Mutators>,
+    mut difficulty: ResMut<Difficulty>,
+    mut adaptive: ResMut<AdaptiveDifficulty>,
+    mut coop: ResMut<Coop>,
+    mut bonuses: ResMut<StartingBonuses>,
+    mut seed: ResMut<RunSeed>,
+) {
+    let Some(daily) = daily else {
+        return;
+    };
+
+    let previous = daily.previous.clone();
+    *mode = previous.mode;
+    *mutators = previous.mutators;
+    *difficulty = previous.difficulty;
+    adaptive.enabled = previous.adaptive;
+    *coop = previous.coop;
+    // The meta progression may have changed during the challenge
+    *bonuses = meta.starting_bonuses();
+    *seed = previous.seed;
+
+    commands.remove_resource::<DailyRun>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn same_date_gives_the_same_challenge() {
+        assert_eq!(
+            DailyChallenge::for_date(date(2024, 5, 17)),
+            DailyChallenge::for_date(date(2024, 5, 17))
+        );
+    }
+
+    #[test]
+    fn challenge_is_a_solo_mode_with_its_mutators() {
+        for day in 1..=28 {
+            let challenge = DailyChallenge::for_date(date(2024, 2, day));
+            assert!(GameMode::SOLO.contains(&challenge.mode));
+            assert_eq!(challenge.mutators.len(), DailyChallenge::MUTATORS);
+        }
+    }
+
+    #[test]
+    fn different_dates_have_different_seeds() {
+        let a = DailyChallenge::for_date(date(2024, 5, 17));
+        let b = DailyChallenge::for_date(date(2024, 5, 18));
+        assert_ne!(a.seed, b.seed);
+        assert_ne!(a.key(), b.key());
+    }
+
+    #[test]
+    fn practice_scores_are_sorted_best_first() {
+        let challenge = DailyChallenge::for_date(date(2024, 5, 17));
+        let mut history = DailyHistory::default();
+        for score in [30, 50, 10, 50] {
+            history.record(&challenge, false, score);
+        }
+
+        let day = history.day(&challenge);
+        assert_eq!(day.practice_scores, vec![50, 50, 30, 10]);
+        assert_eq!(day.best_practice(), Some(50));
+        assert_eq!(day.official_score, None);
+    }
+
+    #[test]
+    fn practice_scores_keep_the_best_ones() {
+        let challenge = DailyChallenge::for_date(date(2024, 5, 17));
+        let mut history = DailyHistory::default();
+        for score in 0..2 * DailyRecord::PRACTICE_KEPT as u32 {
+            history.record(&challenge, false, score);
+        }
+
+        let scores = history.day(&challenge).practice_scores;
+        assert_eq!(scores.len(), DailyRecord::PRACTICE_KEPT);
+        assert_eq!(scores.last(), Some(&(DailyRecord::PRACTICE_KEPT as u32)));
+    }
+
+    #[test]
+    fn official_score_is_kept_apart() {
+        let challenge = DailyChallenge::for_date(date(2024, 5, 17));
+        let mut history = DailyHistory::default();
+        history.record(&challenge, true, 40);
+        history.record(&challenge, false, 60);
+
+        let day = history.day(&challenge);
+        assert_eq!(day.official_score, Some(40));
+        assert_eq!(day.practice_scores, vec![60]);
+    }
+}
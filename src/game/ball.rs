@@ -1,8 +1,8 @@
 use std::time::Duration;
 
-use bevy::{math::*, prelude::*};
+use bevy::{math::*, prelude::*, sprite::Anchor};
 
-use crate::AppState;
+use crate::{camera, AppState};
 
 use super::*;
 
@@ -16,9 +16,90 @@ impl Ball {
     pub const START: Vec2 = vec2(-70.0, 1.0);
     pub const SIZE: Vec2 = vec2(30.0, 30.0);
     pub const ENLARGED_SIZE: Vec2 = vec2(60.0, 60.0);
-    pub const SPEED: f32 = 400.0;
     pub const DIRECTION: Vec2 = vec2(0.5, -0.5);
-    pub const TIMEOUT: f32 = 10.0;
+    pub const ATTACK: u32 = 1;
+
+    pub fn radius(&self) -> f32 {
+        self.size.x / 2.
+    }
+
+    /// Where a new ball of [Ball::SIZE] moving towards `direction` is spawned
+    /// from this one, so that the two don't start overlapping.
+    pub fn spawn_translation(
+        &self,
+        translation: Vec3,
+        direction: Vec2,
+        main_box: &MainBox,
+    ) -> Vec3 {
+        let offset = self.radius() + Ball::SIZE.x / 2. + 1.0;
+        let bound = 0.5 * (main_box.size - Ball::SIZE);
+        let pos = translation.xy() + direction.normalize_or_zero() * offset;
+
+        pos.clamp(-bound, bound).extend(translation.z)
+    }
+}
+
+/// Speed shared by every ball. It grows with paddle hits and time, up to the
+/// [Difficulty] cap, and goes back to the [Tuning] speed when a ball is lost.
+#[derive(Resource, Clone, Copy, Deref, DerefMut)]
+pub struct BallSpeed(pub f32);
+
+#[derive(Component, Clone)]
+pub struct BallSpeedText;
+
+/// Extra speed of a single ball on top of the [BallSpeed], fading over time
+#[derive(Component, Clone, Copy, Default, Deref, DerefMut)]
+pub struct SpeedBoost(pub f32);
+
+impl SpeedBoost {
+    /// Boost lost every second
+    pub const DECAY: f32 = 150.0;
+}
+
+/// Target speed last given to a ball by [apply_ball_speed], `None` until the
+/// ball is first scaled to the [BallSpeed]
+#[derive(Component, Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct AppliedSpeed(pub Option<f32>);
+
+/// Paddle that last bounced the ball, credited for the blocks it destroys
+#[derive(Component, Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct LastPaddle(pub Option<Player>);
+
+/// Optional ball behaviours
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct BallPhysics {
+    /// Balls bounce on each other instead of passing through
+    pub ball_collisions: bool,
+}
+
+impl Default for BallPhysics {
+    fn default() -> Self {
+        Self {
+            ball_collisions: Tuning::default().ball_collisions,
+        }
+    }
+}
+
+impl BallSpeed {
+    pub const PER_PADDLE_HIT: f32 = 10.0;
+    pub const PER_SECOND: f32 = 2.0;
+    /// Bounds of the speed of a ball relative to its target, whatever the
+    /// collisions between balls gave it
+    pub const MIN_RATIO: f32 = 0.5;
+    pub const MAX_RATIO: f32 = 2.0;
+    pub const FONT_SIZE: f32 = 30.0;
+
+    /// Speed the balls are actually given, with the [Stat::BallSpeed] modifiers
+    pub fn applied(self, modifiers: &Modifiers) -> f32 {
+        modifiers.apply(Stat::BallSpeed, *self)
+    }
+}
+
+/// A ball fell below the [MainBox], or above it in versus games
+#[derive(Event, Clone, Copy)]
+pub struct BallLost {
+    /// Player defending the edge the ball went past, only set in versus games
+    pub defender: Option<Player>,
 }
 
 #[derive(Resource, Default, Deref, DerefMut)]
@@ -28,7 +109,11 @@ pub struct BallCollision(pub Handle<AudioSource>);
 pub struct BallBundle {
     pub ball: Ball,
     pub velocity: Velocity,
+    pub boost: SpeedBoost,
+    pub applied_speed: AppliedSpeed,
     pub attack: Attack,
+    pub damage_type: DamageType,
+    pub last_paddle: LastPaddle,
     pub sprite: SpriteBundle,
 }
 
@@ -44,14 +129,25 @@ impl BallBundle {
             ..default()
         }
     }
+
+    pub fn with_damage_type(mut self, damage_type: DamageType) -> Self {
+        self.damage_type = damage_type;
+        self.sprite.sprite.color = damage_type.ball_color();
+        self
+    }
 }
 
 impl Default for BallBundle {
     fn default() -> Self {
         Self {
             ball: Ball { size: Ball::SIZE },
-            attack: Attack(1),
-            velocity: Velocity(Ball::SPEED * Ball::DIRECTION),
+            boost: SpeedBoost::default(),
+            applied_speed: AppliedSpeed::default(),
+            attack: Attack(Ball::ATTACK),
+            damage_type: DamageType::default(),
+            last_paddle: LastPaddle::default(),
+            // Scaled to the BallSpeed by apply_ball_speed
+            velocity: Velocity(Ball::DIRECTION),
             sprite: SpriteBundle {
                 transform: Transform::from_translation(Ball::START.extend(0.0)),
                 sprite: Sprite {
@@ -70,10 +166,10 @@ pub struct BallEnlargmentTimer {
     pub timer: Timer,
 }
 
-impl Default for BallEnlargmentTimer {
-    fn default() -> Self {
+impl BallEnlargmentTimer {
+    pub fn from_secs(secs: f32) -> Self {
         Self {
-            timer: Timer::new(Duration::from_secs_f32(Ball::TIMEOUT), TimerMode::Once),
+            timer: Timer::new(Duration::from_secs_f32(secs), TimerMode::Once),
         }
     }
 }
@@ -82,8 +178,30 @@ pub struct BallPlugin;
 
 impl Plugin for BallPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_ball)
-            .add_systems(Update, (handle_ball_timer).run_if(in_state(AppState::Game)));
+        app.add_event::<BallLost>()
+            .init_resource::<BallPhysics>()
+            .add_systems(Startup, setup_ball)
+            .add_systems(OnEnter(AppState::Game), setup_ball_speed)
+            .add_systems(OnExit(AppState::Game), cleanup_ball_speed)
+            .add_systems(
+                FixedUpdate,
+                (
+                    progress_ball_speed
+                        .after(check_ball_collision)
+                        .after(check_ball_out_of_bound),
+                    apply_ball_speed.after(progress_ball_speed),
+                    collide_balls
+                        .after(apply_velocity)
+                        .before(check_ball_collision)
+                        .run_if(|physics: Res<BallPhysics>| physics.ball_collisions),
+                )
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                Update,
+                (handle_ball_timer, update_ball_speed_text, decay_speed_boost)
+                    .run_if(in_state(AppState::Game)),
+            );
     }
 }
 
@@ -113,3 +231,131 @@ fn handle_ball_timer(
 
     commands.remove_resource::<BallEnlargmentTimer>();
 }
+
+fn setup_ball_speed(mut commands: Commands, tuning: Res<Tuning>, difficulty: Res<Difficulty>) {
+    commands.insert_resource(BallSpeed(
+        tuning.ball_speed * difficulty.ball_speed_factor(),
+    ));
+
+    commands.spawn((
+        BallSpeedText,
+        Text2dBundle {
+            text: Text::from_sections([
+                TextSection::new(
+                    "Speed: ",
+                    TextStyle {
+                        font_size: BallSpeed::FONT_SIZE,
+                        color: Scoreboard::TEXT_COLOR,
+                        ..default()
+                    },
+                ),
+                TextSection::from_style(TextStyle {
+                    font_size: BallSpeed::FONT_SIZE,
+                    color: Scoreboard::SCORE_COLOR,
+                    ..default()
+                }),
+            ]),
+            transform: Transform::from_translation(vec3(0.0, 0.5 * camera::WINDOW_SIZE.y, 0.0)),
+            text_anchor: Anchor::TopCenter,
+            ..default()
+        },
+    ));
+}
+
+fn cleanup_ball_speed(mut commands: Commands, query: Query<Entity, With<BallSpeedText>>) {
+    commands.remove_resource::<BallSpeed>();
+
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+}
+
+pub fn progress_ball_speed(
+    mut speed: ResMut<BallSpeed>,
+    difficulty: Res<Difficulty>,
+    tuning: Res<Tuning>,
+    time: Res<Time>,
+    mut paddle_hit: EventReader<PaddleHit>,
+    mut ball_lost: EventReader<BallLost>,
+) {
+    if ball_lost.read().count() > 0 {
+        **speed = tuning.ball_speed * difficulty.ball_speed_factor();
+        return;
+    }
+
+    let hits = paddle_hit.read().count() as f32;
+    **speed += hits * BallSpeed::PER_PADDLE_HIT + time.delta_seconds() * BallSpeed::PER_SECOND;
+    **speed = speed.min(difficulty.ball_speed_cap());
+}
+
+/// Bring the balls just spawned to the current [BallSpeed] and follow its
+/// changes on the others. Only the change of the target is applied, so that a
+/// ball keeps the speed it gained or lost bouncing on other balls.
+fn apply_ball_speed(
+    speed: Res<BallSpeed>,
+    modifiers: Res<Modifiers>,
+    mut query: Query<(&mut Velocity, &SpeedBoost, &mut AppliedSpeed)>,
+) {
+    let speed = speed.applied(&modifiers);
+
+    for (mut velocity, boost, mut applied) in &mut query {
+        let target = speed + **boost;
+        let current = match **applied {
+            Some(previous) => velocity.length() + target - previous,
+            None => target,
+        };
+        let current = current.clamp(BallSpeed::MIN_RATIO * target, BallSpeed::MAX_RATIO * target);
+
+        velocity.0 = velocity.normalize_or_zero() * current;
+        **applied = Some(target);
+    }
+}
+
+fn decay_speed_boost(time: Res<Time>, mut query: Query<&mut SpeedBoost>) {
+    for mut boost in &mut query {
+        if **boost > 0.0 {
+            **boost = (**boost - SpeedBoost::DECAY * time.delta_seconds()).max(0.0);
+        }
+    }
+}
+
+fn update_ball_speed_text(
+    speed: Res<BallSpeed>,
+    modifiers: Res<Modifiers>,
+    mut query: Query<&mut Text, With<BallSpeedText>>,
+) {
+    let mut text = query.single_mut();
+    text.sections[1].value = format!("{:.0}", speed.applied(&modifiers));
+}
+
+/// Elastic collisions between balls, the mass of a ball is its area
+fn collide_balls(mut query: Query<(&mut Transform, &mut Velocity, &Ball)>) {
+    let mut combinations = query.iter_combinations_mut();
+
+    while let Some([(mut t1, mut v1, b1), (mut t2, mut v2, b2)]) = combinations.fetch_next() {
+        let delta = t2.translation.xy() - t1.translation.xy();
+        let dist = delta.length();
+        let min_dist = b1.radius() + b2.radius();
+        if dist >= min_dist || dist == 0.0 {
+            continue;
+        }
+
+        let normal = delta / dist;
+        let (m1, m2) = (b1.radius().powi(2), b2.radius().powi(2));
+        let tot = m1 + m2;
+
+        // Push the balls apart so they don't stay stuck together
+        let overlap = min_dist - dist;
+        t1.translation -= (normal * overlap * m2 / tot).extend(0.0);
+        t2.translation += (normal * overlap * m1 / tot).extend(0.0);
+
+        let approaching = (v1.0 - v2.0).dot(normal);
+        if approaching <= 0.0 {
+            continue;
+        }
+
+        let impulse = 2.0 * approaching / tot;
+        v1.0 -= impulse * m2 * normal;
+        v2.0 += impulse * m1 * normal;
+    }
+}
@@ -1,4 +1,6 @@
 use bevy::{math::vec2, prelude::*};
+use rand::prelude::*;
+use serde::Serialize;
 
 use super::*;
 
@@ -6,7 +8,11 @@ pub struct BlockPlugin;
 
 impl Plugin for BlockPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::Game), setup_block)
+        app.add_event::<BlockDamaged>()
+            .add_event::<BlockHit>()
+            .add_event::<BlockDestroyed>()
+            .add_event::<BlockRowSpawned>()
+            .add_systems(OnEnter(AppState::Game), setup_block)
             .add_systems(
                 Update,
                 (spawn_new_blocks.after(block_go_down)).run_if(in_state(AppState::Game)),
@@ -15,8 +21,111 @@ impl Plugin for BlockPlugin {
     }
 }
 
+const BLOCK_KIND_RNGS: [(BlockKind, f32); 3] = [
+    (BlockKind::Basic, 0.75),
+    (BlockKind::Hard, 0.2),
+    (BlockKind::Gold, 0.05),
+];
+
 #[derive(Component, Clone)]
-pub struct Block;
+pub struct Block {
+    pub kind: BlockKind,
+}
+
+/// Damage dealt to a block, by a ball or by a status effect
+#[derive(Event, Clone, Copy)]
+pub struct BlockDamaged {
+    pub block: Entity,
+    /// Ball that hit the block, `None` for status effects
+    pub ball: Option<Entity>,
+    /// Paddle that last bounced the ball, or that applied the status effect.
+    /// `None` if no paddle touched the ball yet.
+    pub player: Option<Player>,
+    pub damage: u32,
+    pub crit: bool,
+    pub damage_type: DamageType,
+    pub effectiveness: Effectiveness,
+    /// Where the damage is shown
+    pub translation: Vec3,
+}
+
+/// A ball damaged a block
+#[derive(Event, Clone, Copy)]
+pub struct BlockHit;
+
+/// A block ran out of [Health]
+#[derive(Event, Clone, Copy)]
+pub struct BlockDestroyed {
+    /// Ball that destroyed the block, `None` for status effects
+    pub ball: Option<Entity>,
+    /// Credited for the block
+    pub player: Player,
+    pub kind: BlockKind,
+    pub translation: Vec3,
+}
+
+/// A new row of blocks appeared at the top of the [MainBox]
+#[derive(Event, Clone, Copy)]
+pub struct BlockRowSpawned;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub enum BlockKind {
+    #[default]
+    Basic,
+    Hard,
+    Gold,
+}
+
+impl BlockKind {
+    pub fn health(self) -> u32 {
+        match self {
+            BlockKind::Basic => 1,
+            BlockKind::Hard => 3,
+            BlockKind::Gold => 2,
+        }
+    }
+
+    /// Score awarded when a block of this kind is destroyed
+    pub fn points(self) -> u32 {
+        match self {
+            BlockKind::Basic => 10,
+            BlockKind::Hard => 30,
+            BlockKind::Gold => 100,
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            BlockKind::Basic => Color::NAVY,
+            BlockKind::Hard => Color::DARK_GRAY,
+            BlockKind::Gold => Color::GOLD,
+        }
+    }
+
+    /// Damage taken from a hit of `damage_type` relative to a physical hit
+    pub fn damage_multiplier(self, damage_type: DamageType) -> f32 {
+        match (self, damage_type) {
+            (BlockKind::Hard, DamageType::Physical) => 0.5,
+            (BlockKind::Hard, DamageType::Ice) => 2.0,
+            (BlockKind::Gold, DamageType::Fire) => 2.0,
+            (BlockKind::Gold, DamageType::Ice) => 0.5,
+            _ => 1.0,
+        }
+    }
+
+    /// Get a random [BlockKind]
+    pub fn next_rng(rng: &mut impl Rng) -> Self {
+        let mut prob = rng.gen::<f32>();
+
+        for (kind, weight) in BLOCK_KIND_RNGS {
+            if prob < weight {
+                return kind;
+            }
+            prob -= weight;
+        }
+        BlockKind::Basic
+    }
+}
 
 #[derive(Bundle, Clone)]
 pub struct BlockBundle {
@@ -43,17 +152,15 @@ impl Block {
         25.0,
     );
     pub const PADDING: f32 = 5.0;
-
-    pub const GO_DOWN_TIMEOUT: Duration = Duration::from_secs(10);
-
-    pub const SPAWN_AFTER_GO_DOWN: u32 = 2;
 }
 
 impl Default for BlockBundle {
     fn default() -> Self {
         Self {
-            block: Block,
-            health: Health(1),
+            block: Block {
+                kind: BlockKind::Basic,
+            },
+            health: Health(BlockKind::Basic.health()),
             collider: Collider { size: Block::SIZE },
             sprite: SpriteBundle {
                 transform: Transform::default(),
@@ -69,26 +176,31 @@ impl Default for BlockBundle {
 }
 
 impl BlockBundle {
-    pub fn from_translation(translation: Vec3) -> Self {
+    pub fn from_kind_translation(kind: BlockKind, translation: Vec3) -> Self {
         Self {
+            block: Block { kind },
+            health: Health(kind.health()),
             sprite: {
                 let mut sprite = Self::default().sprite;
                 sprite.transform.translation = translation;
+                sprite.sprite.color = kind.color();
                 sprite
             },
             ..default()
         }
     }
-}
 
-impl Default for BlockGoDown {
-    fn default() -> Self {
-        Self(Timer::new(Block::GO_DOWN_TIMEOUT, TimerMode::Repeating))
+    /// Block of a random [BlockKind]
+    pub fn from_translation(translation: Vec3, rng: &mut impl Rng) -> Self {
+        Self::from_kind_translation(BlockKind::next_rng(rng), translation)
     }
 }
 
-fn setup_block(mut commands: Commands) {
-    commands.init_resource::<BlockGoDown>();
+fn setup_block(mut commands: Commands, tuning: Res<Tuning>) {
+    commands.insert_resource(BlockGoDown(Timer::new(
+        tuning.block_go_down_timeout(),
+        TimerMode::Repeating,
+    )));
     commands.init_resource::<BlockSpawn>();
 }
 
@@ -97,10 +209,12 @@ fn cleanup_block(mut commands: Commands) {
     commands.remove_resource::<BlockSpawn>();
 }
 
+/// Move the blocks down a row, except the columns holding a [Frozen] block so
+/// that no block moves into an occupied cell
 pub fn block_go_down(
     mut timer: ResMut<BlockGoDown>,
     time: Res<Time>,
-    mut query: Query<&mut Transform, With<Block>>,
+    mut query: Query<(&mut Transform, Has<Frozen>), With<Block>>,
 ) {
     timer.tick(time.delta());
 
@@ -108,32 +222,54 @@ pub fn block_go_down(
         return;
     }
 
-    for mut transform in &mut query {
-        transform.translation.y -= (Block::SIZE.y + Block::PADDING) as f32;
+    let frozen_columns: Vec<f32> = query
+        .iter()
+        .filter(|(_, frozen)| *frozen)
+        .map(|(transform, _)| transform.translation.x)
+        .collect();
+
+    for (mut transform, _) in &mut query {
+        let x = transform.translation.x;
+        if frozen_columns.iter().any(|&col| (col - x).abs() < 1.0) {
+            continue;
+        }
+
+        transform.translation.y -= Block::SIZE.y + Block::PADDING;
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_new_blocks(
     mut commands: Commands,
     mut block_spawn: ResMut<BlockSpawn>,
     go_down: Res<BlockGoDown>,
     main_box: Res<MainBox>,
+    tuning: Res<Tuning>,
+    mut rng: ResMut<GameRng>,
+    mut row_spawned: EventWriter<BlockRowSpawned>,
+    blocks: Query<&Transform, With<Block>>,
+    bosses: Query<(), With<Boss>>,
 ) {
     if !go_down.just_finished() {
         return;
     }
 
-    block_spawn.go_down_counter = (block_spawn.go_down_counter + 1) % Block::SPAWN_AFTER_GO_DOWN;
+    block_spawn.go_down_counter =
+        (block_spawn.go_down_counter + 1) % tuning.block_spawn_after_go_down;
 
     let spawn = block_spawn.go_down_counter == 0;
-    if !spawn {
+    // The top lane belongs to the boss until it is defeated
+    if !spawn || !bosses.is_empty() {
         return;
     }
 
-    commands.spawn_batch(spawn_column(main_box));
+    let occupied: Vec<Vec2> = blocks.iter().map(|t| t.translation.xy()).collect();
+    commands.spawn_batch(spawn_column(main_box, &occupied, &mut rng.field));
+    row_spawned.send(BlockRowSpawned);
 }
 
-fn spawn_column(main_box: Res<MainBox>) -> Vec<BlockBundle> {
+/// New top row of blocks, leaving out the cells still `occupied` by a held column
+fn spawn_column(main_box: Res<MainBox>, occupied: &[Vec2], rng: &mut impl Rng) -> Vec<BlockBundle> {
     let mut blocks = Vec::with_capacity(Block::HEIGHT as usize);
     let mut wall_top_right = 0.5 * vec3(-main_box.size.x, main_box.size.y, 0.0);
     // Add a half of a block size
@@ -147,8 +283,11 @@ fn spawn_column(main_box: Res<MainBox>) -> Vec<BlockBundle> {
         let mut pos = vec3(w as f32 * (Block::SIZE.x + Block::PADDING), 0.0, 0.0);
 
         pos += wall_top_right;
+        if occupied.iter().any(|cell| cell.distance(pos.xy()) < 1.0) {
+            continue;
+        }
 
-        blocks.push(BlockBundle::from_translation(pos));
+        blocks.push(BlockBundle::from_translation(pos, rng));
     }
 
     blocks
@@ -0,0 +1,520 @@
+use std::time::Duration;
+
+use bevy::{
+    math::{
+        bounding::{Aabb2d, BoundingCircle},
+        vec2, vec3,
+    },
+    prelude::*,
+    sprite::Anchor,
+};
+use rand::prelude::*;
+
+use crate::{camera, AppState};
+
+use super::{
+    apply_velocity,
+    ball::{Ball, BallCollision, LastPaddle},
+    block::{Block, BlockDamaged, BlockRowSpawned},
+    cleanup_component, collide_volume,
+    damage::{scaled_damage, DamageRoll, DamageType},
+    damage_blocks,
+    paddle::{ExpUp, Paddle, PaddleResizeTimer, PaddleStun, Player},
+    powerup::Powerup,
+    reflect_velocity,
+    rng::GameRng,
+    skill::Modifiers,
+    tuning::Tuning,
+    Attack, Collider, Health, MainBox, PlayerCollider, Velocity,
+};
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BossDefeated>()
+            .add_event::<PaddleHitByProjectile>()
+            .add_systems(OnEnter(AppState::Game), setup_boss_spawn)
+            .add_systems(
+                Update,
+                (spawn_boss, move_boss, boss_attack, update_boss_health_bar)
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    check_boss_hits.after(apply_velocity).before(damage_blocks),
+                    reward_boss_defeat.after(damage_blocks),
+                    check_projectile_hits.after(apply_velocity),
+                )
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                OnExit(AppState::Game),
+                (
+                    cleanup_component::<Boss>,
+                    cleanup_component::<BossCell>,
+                    cleanup_component::<BossProjectile>,
+                    cleanup_boss_health_bar,
+                ),
+            );
+    }
+}
+
+/// A boss made of [Boss::CELLS] cells, moving in a lane at the top of the
+/// [MainBox]. The cells are the colliders hit by the balls, every hit damages
+/// the [Health] of the boss.
+#[derive(Component, Clone)]
+pub struct Boss {
+    pub max_health: u32,
+    pub direction: f32,
+    pub attack: Timer,
+}
+
+#[derive(Component, Clone)]
+pub struct BossCell {
+    pub boss: Entity,
+    pub offset: Vec2,
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub enum BossProjectile {
+    /// The paddle can't move for a while
+    Stun,
+    /// The paddle gets smaller for a while
+    Shrink,
+}
+
+impl BossProjectile {
+    pub fn damage_type(self) -> DamageType {
+        match self {
+            BossProjectile::Stun => DamageType::Ice,
+            BossProjectile::Shrink => DamageType::Poison,
+        }
+    }
+}
+
+/// A boss ran out of [Health], sent by [damage_blocks]
+#[derive(Event, Clone, Copy)]
+pub struct BossDefeated {
+    pub boss: Entity,
+    /// Credited for the defeat
+    pub player: Player,
+    pub translation: Vec3,
+}
+
+/// A boss projectile hit a paddle
+#[derive(Event, Clone, Copy)]
+pub struct PaddleHitByProjectile;
+
+#[derive(Component, Clone)]
+pub struct BossHealthBar {
+    max_len: f32,
+}
+
+/// Block rows spawned since the last boss
+#[derive(Resource, Default, Clone, Copy)]
+pub struct BossSpawn {
+    pub rows: u32,
+}
+
+impl Boss {
+    /// A boss appears every this many block rows
+    pub const EVERY_ROWS: u32 = 5;
+    pub const CELLS: u32 = 3;
+    pub const CELL_SIZE: Vec2 = vec2(60.0, 40.0);
+    pub const HEALTH: u32 = 40;
+    pub const SPEED: f32 = 120.0;
+    pub const COLOR: Color = Color::MAROON;
+    pub const ATTACK_INTERVAL: Duration = Duration::from_secs(3);
+    pub const EXP_BONUS: u32 = 30;
+    pub const POWERUP_DROPS: u32 = 3;
+    /// Score awarded for the defeat, like a destroyed block
+    pub const POINTS: u32 = 500;
+    /// Block rows pushed down to make room for the lane of the boss
+    pub const LANE_ROWS: u32 = 2;
+
+    pub const PROJECTILE_SIZE: Vec2 = vec2(12.0, 12.0);
+    pub const PROJECTILE_SPEED: f32 = 250.0;
+    pub const STUN_DURATION: Duration = Duration::from_secs(1);
+
+    pub const BAR_SIZE: Vec2 = vec2(400.0, 10.0);
+
+    fn width() -> f32 {
+        Self::CELLS as f32 * Self::CELL_SIZE.x
+    }
+
+    /// Damage taken from a hit of `damage_type` relative to a physical hit
+    pub fn damage_multiplier(damage_type: DamageType) -> f32 {
+        match damage_type {
+            DamageType::Physical => 1.0,
+            DamageType::Fire => 1.5,
+            DamageType::Ice | DamageType::Poison => 0.5,
+        }
+    }
+}
+
+fn setup_boss_spawn(mut commands: Commands) {
+    commands.insert_resource(BossSpawn::default());
+}
+
+fn spawn_boss(
+    mut commands: Commands,
+    mut boss_spawn: ResMut<BossSpawn>,
+    mut row_spawned: EventReader<BlockRowSpawned>,
+    main_box: Res<MainBox>,
+    bosses: Query<(), With<Boss>>,
+    mut blocks: Query<&mut Transform, With<Block>>,
+) {
+    boss_spawn.rows += row_spawned.read().count() as u32;
+
+    if boss_spawn.rows < Boss::EVERY_ROWS || !bosses.is_empty() {
+        return;
+    }
+    boss_spawn.rows = 0;
+
+    // Make room in the top lane, no new row comes down while the boss is alive
+    for mut transform in &mut blocks {
+        transform.translation.y -= Boss::LANE_ROWS as f32 * (Block::SIZE.y + Block::PADDING);
+    }
+    let translation = vec3(
+        0.0,
+        0.5 * main_box.size.y - Block::PADDING - 0.5 * Boss::CELL_SIZE.y,
+        1.0,
+    );
+
+    let boss = commands
+        .spawn((
+            Boss {
+                max_health: Boss::HEALTH,
+                direction: 1.0,
+                attack: Timer::new(Boss::ATTACK_INTERVAL, TimerMode::Repeating),
+            },
+            Health(Boss::HEALTH),
+            SpatialBundle::from_transform(Transform::from_translation(translation)),
+        ))
+        .id();
+
+    for i in 0..Boss::CELLS {
+        let offset = vec2(
+            (i as f32 + 0.5) * Boss::CELL_SIZE.x - 0.5 * Boss::width(),
+            0.0,
+        );
+
+        commands.spawn((
+            BossCell { boss, offset },
+            Collider {
+                size: Boss::CELL_SIZE,
+            },
+            SpriteBundle {
+                transform: Transform::from_translation(translation + offset.extend(0.0)),
+                sprite: Sprite {
+                    color: Boss::COLOR,
+                    custom_size: Some(Boss::CELL_SIZE - 2.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    }
+
+    spawn_boss_health_bar(&mut commands);
+}
+
+fn spawn_boss_health_bar(commands: &mut Commands) {
+    let translation = vec3(
+        -0.5 * Boss::BAR_SIZE.x,
+        0.5 * camera::WINDOW_SIZE.y - 35.0,
+        0.0,
+    );
+
+    commands
+        .spawn((
+            BossHealthBar { max_len: 0.0 },
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::DARK_GRAY,
+                    anchor: Anchor::CenterLeft,
+                    custom_size: Some(Boss::BAR_SIZE),
+                    ..default()
+                },
+                transform: Transform::from_translation(translation),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                BossHealthBar {
+                    max_len: Boss::BAR_SIZE.x,
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::RED,
+                        anchor: Anchor::CenterLeft,
+                        custom_size: Some(Boss::BAR_SIZE),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(Vec3::Z),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn cleanup_boss_health_bar(
+    mut commands: Commands,
+    query: Query<Entity, (With<BossHealthBar>, Without<Parent>)>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Move the boss from side to side of the [MainBox] and its cells with it
+fn move_boss(
+    time: Res<Time>,
+    main_box: Res<MainBox>,
+    mut bosses: Query<(&mut Transform, &mut Boss), Without<BossCell>>,
+    mut cells: Query<(&mut Transform, &BossCell)>,
+) {
+    let max_x = 0.5 * (main_box.size.x - Boss::width());
+
+    for (mut transform, mut boss) in &mut bosses {
+        transform.translation.x += boss.direction * Boss::SPEED * time.delta_seconds();
+
+        if transform.translation.x.abs() > max_x {
+            transform.translation.x = transform.translation.x.clamp(-max_x, max_x);
+            boss.direction = -boss.direction;
+        }
+    }
+
+    for (mut transform, cell) in &mut cells {
+        if let Ok((boss_t, _)) = bosses.get(cell.boss) {
+            transform.translation = boss_t.translation + cell.offset.extend(0.0);
+        }
+    }
+}
+
+/// Fire projectiles toward the paddle
+fn boss_attack(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bosses: Query<(&Transform, &mut Boss)>,
+    paddles: Query<&Transform, With<Paddle>>,
+    mut rng: ResMut<GameRng>,
+) {
+    for (transform, mut boss) in &mut bosses {
+        boss.attack.tick(time.delta());
+        if !boss.attack.just_finished() {
+            continue;
+        }
+
+        // Aim at a random paddle
+        let Some(paddle_t) = paddles.iter().choose(&mut rng.combat) else {
+            return;
+        };
+
+        let projectile = if rng.combat.gen_bool(0.5) {
+            BossProjectile::Stun
+        } else {
+            BossProjectile::Shrink
+        };
+        let damage_type = projectile.damage_type();
+        let direction = (paddle_t.translation - transform.translation)
+            .truncate()
+            .try_normalize()
+            .unwrap_or(Vec2::NEG_Y);
+
+        commands.spawn((
+            projectile,
+            damage_type,
+            PlayerCollider(Collider {
+                size: Boss::PROJECTILE_SIZE,
+            }),
+            Velocity(direction * Boss::PROJECTILE_SPEED),
+            SpriteBundle {
+                transform: Transform::from_translation(transform.translation),
+                sprite: Sprite {
+                    color: damage_type.color(),
+                    custom_size: Some(Boss::PROJECTILE_SIZE),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    }
+}
+
+fn check_projectile_hits(
+    mut commands: Commands,
+    main_box: Res<MainBox>,
+    tuning: Res<Tuning>,
+    projectiles: Query<(Entity, &Transform, &PlayerCollider, &BossProjectile)>,
+    mut paddles: Query<(Entity, &Transform, &mut Collider, &mut Sprite, &Paddle)>,
+    mut paddle_hit: EventWriter<PaddleHitByProjectile>,
+) {
+    for (entity, transform, collider, projectile) in &projectiles {
+        // Out of the MainBox on any side
+        let outside = transform
+            .translation
+            .truncate()
+            .abs()
+            .cmpgt(0.5 * main_box.size)
+            .any();
+        if outside {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let hit = paddles.iter_mut().find(|(_, paddle_t, paddle_c, _, _)| {
+            collide_volume(
+                Aabb2d::new(transform.translation.truncate(), collider.size / 2.),
+                Aabb2d::new(paddle_t.translation.truncate(), paddle_c.size / 2.),
+            )
+            .is_some()
+        });
+        let Some((paddle_entity, _, mut paddle_collider, mut paddle_sprite, paddle)) = hit else {
+            continue;
+        };
+
+        match projectile {
+            BossProjectile::Stun => {
+                commands
+                    .entity(paddle_entity)
+                    .insert(PaddleStun(Timer::new(Boss::STUN_DURATION, TimerMode::Once)));
+            }
+            BossProjectile::Shrink => {
+                let size = paddle.resized(Paddle::SHRINK_FACTOR);
+                paddle_sprite.custom_size = Some(size);
+                paddle_collider.size = size;
+                commands
+                    .entity(paddle_entity)
+                    .insert(PaddleResizeTimer::from_secs(tuning.paddle_resize_timeout));
+            }
+        }
+
+        paddle_hit.send(PaddleHitByProjectile);
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Bounce the balls on the boss cells, the damage goes to the boss through
+/// [damage_blocks] like the damage of a block
+#[allow(clippy::too_many_arguments)]
+fn check_boss_hits(
+    mut commands: Commands,
+    collision_sound: Res<BallCollision>,
+    mut balls: Query<(
+        Entity,
+        &Transform,
+        &mut Velocity,
+        &Attack,
+        &DamageType,
+        &Ball,
+        &LastPaddle,
+    )>,
+    cells: Query<(&Transform, &Collider, &BossCell)>,
+    damage_roll: Res<DamageRoll>,
+    modifiers: Res<Modifiers>,
+    mut rng: ResMut<GameRng>,
+    mut block_damaged: EventWriter<BlockDamaged>,
+) {
+    for (cell_t, collider, cell) in &cells {
+        for (ball_entity, ball_t, mut ball_v, attack, damage_type, ball, last_paddle) in &mut balls
+        {
+            let collision = collide_volume(
+                BoundingCircle::new(ball_t.translation.truncate(), ball.radius()),
+                Aabb2d::new(cell_t.translation.truncate(), collider.size / 2.),
+            );
+            let Some(collision) = collision else { continue };
+
+            reflect_velocity(collision, &mut ball_v);
+
+            commands.spawn(AudioBundle {
+                source: collision_sound.clone(),
+                settings: PlaybackSettings::DESPAWN,
+            });
+
+            let (rolled, crit) = damage_roll.roll(**attack, &modifiers, &mut rng.combat);
+            let (damage, effectiveness) =
+                scaled_damage(rolled, Boss::damage_multiplier(*damage_type));
+            block_damaged.send(BlockDamaged {
+                block: cell.boss,
+                ball: Some(ball_entity),
+                player: **last_paddle,
+                damage,
+                crit,
+                damage_type: *damage_type,
+                effectiveness,
+                translation: cell_t.translation,
+            });
+        }
+    }
+}
+
+/// Award the bonus exp and the guaranteed powerups of the defeated bosses
+fn reward_boss_defeat(
+    mut commands: Commands,
+    mut defeated: EventReader<BossDefeated>,
+    mut exp_up: EventWriter<ExpUp>,
+    mut rng: ResMut<GameRng>,
+) {
+    for defeated in defeated.read() {
+        exp_up.send(ExpUp {
+            player: defeated.player,
+            exp: Boss::EXP_BONUS,
+        });
+        for i in 0..Boss::POWERUP_DROPS {
+            let x = (i as f32 - 0.5 * (Boss::POWERUP_DROPS - 1) as f32) * Boss::CELL_SIZE.x;
+            Powerup::spawn_guaranteed_powerup(
+                &mut commands,
+                defeated.translation + Vec3::X * x,
+                &mut rng.drops,
+            );
+        }
+        commands.entity(defeated.boss).despawn();
+    }
+}
+
+/// Remove the cells and the health bar of the defeated bosses, and shrink the bar
+fn update_boss_health_bar(
+    mut commands: Commands,
+    bosses: Query<(&Health, &Boss)>,
+    cells: Query<(Entity, &BossCell)>,
+    mut bars: Query<(Entity, &mut Sprite, &BossHealthBar, Option<&Parent>)>,
+) {
+    for (entity, cell) in &cells {
+        if bosses.get(cell.boss).is_err() {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let Some((health, boss)) = bosses.iter().next() else {
+        for (entity, _, _, parent) in &bars {
+            if parent.is_none() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+        return;
+    };
+
+    let percentage = **health as f32 / boss.max_health as f32;
+    for (_, mut sprite, bar, parent) in &mut bars {
+        if parent.is_some() {
+            sprite.custom_size = sprite
+                .custom_size
+                .map(|size| vec2(percentage * bar.max_len, size.y));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_lane_fits_a_boss() {
+        let lane = Boss::LANE_ROWS as f32 * (Block::SIZE.y + Block::PADDING);
+        assert!(lane >= Boss::CELL_SIZE.y + Block::PADDING);
+    }
+}
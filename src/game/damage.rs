@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::{
+    ball::Ball,
+    skill::{Modifiers, Stat},
+};
+
+/// Element of the damage dealt by a ball
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DamageType {
+    #[default]
+    Physical,
+    Fire,
+    Ice,
+    Poison,
+}
+
+impl DamageType {
+    /// Color of the damage numbers
+    pub fn color(self) -> Color {
+        match self {
+            DamageType::Physical => Color::RED,
+            DamageType::Fire => Color::ORANGE_RED,
+            DamageType::Ice => Color::CYAN,
+            DamageType::Poison => Color::LIME_GREEN,
+        }
+    }
+
+    /// Tint of the balls dealing this damage
+    pub fn ball_color(self) -> Color {
+        match self {
+            DamageType::Physical => Ball::COLOR,
+            DamageType::Fire => Color::rgb(1.0, 0.2, 0.0),
+            DamageType::Ice => Color::rgb(0.5, 0.8, 1.0),
+            DamageType::Poison => Color::rgb(0.4, 0.8, 0.2),
+        }
+    }
+}
+
+/// How a hit was affected by the resistances of its target
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Effectiveness {
+    Resisted,
+    #[default]
+    Normal,
+    Effective,
+}
+
+/// Damage of an `attack` scaled by the `multiplier` of the target against its
+/// [DamageType]. A hit always deals at least 1 damage.
+pub fn scaled_damage(attack: u32, multiplier: f32) -> (u32, Effectiveness) {
+    let damage = ((attack as f32 * multiplier).round() as u32).max(1);
+    let effectiveness = if multiplier > 1.0 {
+        Effectiveness::Effective
+    } else if multiplier < 1.0 {
+        Effectiveness::Resisted
+    } else {
+        Effectiveness::Normal
+    };
+
+    (damage, effectiveness)
+}
+
+/// Base crit and variance of every hit, before the [Modifiers]
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct DamageRoll {
+    /// Probability of a crit, from 0 to 1
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
+    /// Fraction of the attack randomly added or removed, 0 disables the variance
+    pub variance: f32,
+}
+
+impl Default for DamageRoll {
+    fn default() -> Self {
+        Self {
+            crit_chance: 0.05,
+            crit_multiplier: 1.5,
+            variance: 0.2,
+        }
+    }
+}
+
+impl DamageRoll {
+    /// Damage of a hit with `attack`, and whether it was a crit
+    pub fn roll(&self, attack: u32, modifiers: &Modifiers, rng: &mut impl Rng) -> (u32, bool) {
+        let crit_chance = modifiers.apply(Stat::CritChance, self.crit_chance);
+        let variance = modifiers.apply(Stat::DamageVariance, self.variance);
+
+        let mut damage = attack as f32;
+        if variance > 0.0 {
+            damage *= 1.0 + rng.gen_range(-variance..=variance);
+        }
+
+        let crit = rng.gen::<f32>() < crit_chance;
+        if crit {
+            damage *= modifiers.apply(Stat::CritMultiplier, self.crit_multiplier);
+        }
+
+        ((damage.round() as u32).max(1), crit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neutral_multiplier_keeps_the_attack() {
+        assert_eq!(scaled_damage(4, 1.0), (4, Effectiveness::Normal));
+    }
+
+    #[test]
+    fn weakness_is_effective() {
+        assert_eq!(scaled_damage(4, 1.5), (6, Effectiveness::Effective));
+    }
+
+    #[test]
+    fn resistance_is_resisted_and_rounded() {
+        assert_eq!(scaled_damage(5, 0.5), (3, Effectiveness::Resisted));
+    }
+
+    #[test]
+    fn a_hit_deals_at_least_one_damage() {
+        assert_eq!(scaled_damage(1, 0.25), (1, Effectiveness::Resisted));
+        assert_eq!(scaled_damage(3, 0.0), (1, Effectiveness::Resisted));
+    }
+}
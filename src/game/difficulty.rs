@@ -0,0 +1,409 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::AppState;
+
+use super::{
+    ball::BallLost,
+    block::{Block, BlockGoDown},
+    paddle::ExpUp,
+    rng::GameRng,
+    skill::{Modifiers, Stat},
+    tuning::Tuning,
+    Health, MainBox,
+};
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+            .init_resource::<AdaptiveDifficulty>()
+            .add_systems(OnEnter(AppState::Game), reset_adaptive_difficulty)
+            .add_systems(
+                Update,
+                (scale_block_health, apply_descent_interval).run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                FixedUpdate,
+                adapt_difficulty.run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+/// Difficulty picked from the main menu for the next runs
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    /// Maximum speed the balls can reach during a run
+    pub fn ball_speed_cap(self) -> f32 {
+        match self {
+            Difficulty::Easy => 550.0,
+            Difficulty::Normal => 700.0,
+            Difficulty::Hard => 900.0,
+        }
+    }
+
+    /// Scale of the starting ball speed
+    pub fn ball_speed_factor(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.85,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.15,
+        }
+    }
+
+    /// Scale of the time between two descents of the blocks
+    pub fn descent_factor(self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.3,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 0.8,
+        }
+    }
+
+    /// Scale of the block health, a block always keeps at least 1
+    pub fn block_health_factor(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    /// Scale of the powerup drop chance
+    pub fn drop_factor(self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.25,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 0.75,
+        }
+    }
+}
+
+/// Change made by the [AdaptiveDifficulty] during a run
+#[derive(Debug, Clone)]
+pub struct Adjustment {
+    /// Seconds since the start of the run
+    pub at: f32,
+    pub reason: &'static str,
+    pub descent_factor: f32,
+    pub drop_factor: f32,
+}
+
+/// Optional tuning of the descent timer and of the drop rates on top of the
+/// [Difficulty], based on how the player is doing. Every
+/// [AdaptiveDifficulty::INTERVAL] the balls lost, the depth reached by the blocks
+/// and the exp gained are checked, and the factors move by one step towards an
+/// easier or a harder game.
+#[derive(Resource, Debug, Clone)]
+pub struct AdaptiveDifficulty {
+    pub enabled: bool,
+    pub descent_factor: f32,
+    pub drop_factor: f32,
+    pub adjustments: Vec<Adjustment>,
+    elapsed: f32,
+    balls_lost: u32,
+    exp: u32,
+    timer: Timer,
+}
+
+impl Default for AdaptiveDifficulty {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            descent_factor: 1.0,
+            drop_factor: 1.0,
+            adjustments: Vec::new(),
+            elapsed: 0.0,
+            balls_lost: 0,
+            exp: 0,
+            timer: Timer::new(Self::INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+impl AdaptiveDifficulty {
+    pub const INTERVAL: Duration = Duration::from_secs(15);
+    pub const STEP: f32 = 0.1;
+    pub const MIN_FACTOR: f32 = 0.7;
+    pub const MAX_FACTOR: f32 = 1.5;
+
+    /// Struggling above this many balls lost per minute
+    pub const MAX_BALLS_LOST: f32 = 2.0;
+    /// Fraction of the box the blocks descended, struggling above the max
+    /// and cruising below the min
+    pub const MAX_DEPTH: f32 = 0.7;
+    pub const MIN_DEPTH: f32 = 0.4;
+    /// Exp per minute, struggling below the min and cruising above the max
+    pub const MIN_EXP: f32 = 20.0;
+    pub const MAX_EXP: f32 = 60.0;
+
+    /// Same settings, with the run state cleared
+    fn reset(&self) -> Self {
+        Self {
+            enabled: self.enabled,
+            ..default()
+        }
+    }
+
+    /// Step and reason of the adjustment for the rates measured over an
+    /// [AdaptiveDifficulty::INTERVAL], `None` if the game is balanced
+    fn evaluate(balls_lost: f32, depth: f32, exp: f32) -> Option<(f32, &'static str)> {
+        if balls_lost > Self::MAX_BALLS_LOST {
+            Some((Self::STEP, "losing balls"))
+        } else if depth > Self::MAX_DEPTH {
+            Some((Self::STEP, "blocks too low"))
+        } else if exp < Self::MIN_EXP {
+            Some((Self::STEP, "slow progress"))
+        } else if balls_lost == 0.0 && depth < Self::MIN_DEPTH && exp > Self::MAX_EXP {
+            Some((-Self::STEP, "cruising"))
+        } else {
+            None
+        }
+    }
+
+    fn adjust(&mut self, step: f32, reason: &'static str) {
+        let clamp = |f: f32| f.clamp(Self::MIN_FACTOR, Self::MAX_FACTOR);
+        let descent_factor = clamp(self.descent_factor + step);
+        let drop_factor = clamp(self.drop_factor + step);
+
+        if descent_factor == self.descent_factor && drop_factor == self.drop_factor {
+            return;
+        }
+
+        self.descent_factor = descent_factor;
+        self.drop_factor = drop_factor;
+        self.adjustments.push(Adjustment {
+            at: self.elapsed,
+            reason,
+            descent_factor,
+            drop_factor,
+        });
+    }
+
+    /// Lines shown on the end screen
+    pub fn summary(&self) -> Vec<String> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        if self.adjustments.is_empty() {
+            return vec!["Adaptive difficulty: no adjustments".to_string()];
+        }
+
+        let mut lines = vec![format!(
+            "Adaptive difficulty: {} adjustments, descent x{:.1}, drops x{:.1}",
+            self.adjustments.len(),
+            self.descent_factor,
+            self.drop_factor
+        )];
+        for adjustment in self.adjustments.iter().rev().take(3) {
+            let minutes = adjustment.at as u32 / 60;
+            let seconds = adjustment.at as u32 % 60;
+            lines.push(format!(
+                "  {minutes}:{seconds:02} {}: descent x{:.1}, drops x{:.1}",
+                adjustment.reason, adjustment.descent_factor, adjustment.drop_factor
+            ));
+        }
+
+        lines
+    }
+}
+
+fn reset_adaptive_difficulty(mut adaptive: ResMut<AdaptiveDifficulty>) {
+    *adaptive = adaptive.reset();
+}
+
+fn adapt_difficulty(
+    time: Res<Time>,
+    main_box: Res<MainBox>,
+    mut adaptive: ResMut<AdaptiveDifficulty>,
+    mut ball_lost: EventReader<BallLost>,
+    mut exp_up: EventReader<ExpUp>,
+    blocks: Query<&Transform, With<Block>>,
+) {
+    if !adaptive.enabled {
+        return;
+    }
+
+    // Only the adjustments count as a change
+    let counters = adaptive.bypass_change_detection();
+    counters.elapsed += time.delta_seconds();
+    counters.balls_lost += ball_lost.read().count() as u32;
+    counters.exp += exp_up.read().map(|exp| exp.exp).sum::<u32>();
+
+    if !counters.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let minutes = AdaptiveDifficulty::INTERVAL.as_secs_f32() / 60.0;
+    let balls_lost = counters.balls_lost as f32 / minutes;
+    let exp = counters.exp as f32 / minutes;
+    counters.balls_lost = 0;
+    counters.exp = 0;
+
+    let top = 0.5 * main_box.size.y;
+    let lowest = blocks
+        .iter()
+        .map(|t| t.translation.y - 0.5 * Block::SIZE.y)
+        .fold(top, f32::min);
+    let depth = (top - lowest) / main_box.size.y;
+
+    if let Some((step, reason)) = AdaptiveDifficulty::evaluate(balls_lost, depth, exp) {
+        adaptive.adjust(step, reason);
+    }
+}
+
+/// Time between two descents of the blocks from the [Tuning], the [Difficulty]
+/// and the [AdaptiveDifficulty]
+fn apply_descent_interval(
+    tuning: Res<Tuning>,
+    difficulty: Res<Difficulty>,
+    adaptive: Res<AdaptiveDifficulty>,
+    go_down: Option<ResMut<BlockGoDown>>,
+) {
+    let Some(mut go_down) = go_down else { return };
+    if !(go_down.is_added()
+        || tuning.is_changed()
+        || difficulty.is_changed()
+        || adaptive.is_changed())
+    {
+        return;
+    }
+
+    let factor = difficulty.descent_factor() * adaptive.descent_factor;
+    go_down.set_duration(tuning.block_go_down_timeout().mul_f32(factor));
+}
+
+/// Scale the health of the new blocks to the [Difficulty], spread by the
+/// [Stat::BlockHealthVariance]
+fn scale_block_health(
+    difficulty: Res<Difficulty>,
+    modifiers: Res<Modifiers>,
+    mut rng: ResMut<GameRng>,
+    mut query: Query<&mut Health, Added<Block>>,
+) {
+    let variance = modifiers.apply(Stat::BlockHealthVariance, 0.0);
+
+    for mut health in &mut query {
+        let mut scaled = **health as f32 * difficulty.block_health_factor();
+        if variance > 0.0 {
+            scaled *= 1.0 + rng.field.gen_range(-variance..=variance);
+        }
+        **health = (scaled.round() as u32).max(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjust_moves_both_factors_by_a_step() {
+        let mut adaptive = AdaptiveDifficulty::default();
+        adaptive.adjust(AdaptiveDifficulty::STEP, "losing balls");
+
+        assert!((adaptive.descent_factor - 1.1).abs() < 1e-6);
+        assert!((adaptive.drop_factor - 1.1).abs() < 1e-6);
+        assert_eq!(adaptive.adjustments.len(), 1);
+        assert_eq!(adaptive.adjustments[0].reason, "losing balls");
+    }
+
+    #[test]
+    fn adjust_stays_within_bounds() {
+        let mut adaptive = AdaptiveDifficulty::default();
+        for _ in 0..20 {
+            adaptive.adjust(AdaptiveDifficulty::STEP, "losing balls");
+        }
+        assert_eq!(adaptive.descent_factor, AdaptiveDifficulty::MAX_FACTOR);
+        assert_eq!(adaptive.drop_factor, AdaptiveDifficulty::MAX_FACTOR);
+
+        for _ in 0..20 {
+            adaptive.adjust(-AdaptiveDifficulty::STEP, "cruising");
+        }
+        assert_eq!(adaptive.descent_factor, AdaptiveDifficulty::MIN_FACTOR);
+        assert_eq!(adaptive.drop_factor, AdaptiveDifficulty::MIN_FACTOR);
+    }
+
+    #[test]
+    fn adjust_at_a_bound_is_not_recorded() {
+        let mut adaptive = AdaptiveDifficulty {
+            descent_factor: AdaptiveDifficulty::MIN_FACTOR,
+            drop_factor: AdaptiveDifficulty::MIN_FACTOR,
+            ..default()
+        };
+        adaptive.adjust(-AdaptiveDifficulty::STEP, "cruising");
+
+        assert!(adaptive.adjustments.is_empty());
+    }
+
+    #[test]
+    fn reset_keeps_only_the_setting() {
+        let mut adaptive = AdaptiveDifficulty {
+            enabled: true,
+            ..default()
+        };
+        adaptive.adjust(AdaptiveDifficulty::STEP, "losing balls");
+
+        let reset = adaptive.reset();
+        assert!(reset.enabled);
+        assert_eq!(reset.descent_factor, 1.0);
+        assert!(reset.adjustments.is_empty());
+    }
+
+    #[test]
+    fn struggling_is_checked_before_cruising() {
+        let evaluate = AdaptiveDifficulty::evaluate;
+        let step = AdaptiveDifficulty::STEP;
+
+        assert_eq!(evaluate(3.0, 0.2, 100.0), Some((step, "losing balls")));
+        assert_eq!(evaluate(0.0, 0.8, 100.0), Some((step, "blocks too low")));
+        assert_eq!(evaluate(0.0, 0.2, 10.0), Some((step, "slow progress")));
+        assert_eq!(evaluate(0.0, 0.2, 100.0), Some((-step, "cruising")));
+    }
+
+    #[test]
+    fn balanced_game_is_not_adjusted() {
+        let evaluate = AdaptiveDifficulty::evaluate;
+
+        // In between the thresholds
+        assert_eq!(evaluate(1.0, 0.5, 40.0), None);
+        // Cruising needs every rate on the easy side
+        assert_eq!(evaluate(1.0, 0.2, 100.0), None);
+        assert_eq!(evaluate(0.0, 0.5, 100.0), None);
+        assert_eq!(evaluate(0.0, 0.2, 40.0), None);
+        // The thresholds themselves are balanced
+        assert_eq!(
+            evaluate(
+                AdaptiveDifficulty::MAX_BALLS_LOST,
+                AdaptiveDifficulty::MAX_DEPTH,
+                AdaptiveDifficulty::MIN_EXP
+            ),
+            None
+        );
+    }
+}
@@ -4,13 +4,19 @@ use bevy::{math::vec2, prelude::*};
 
 use crate::AppState;
 
-use super::Velocity;
+use super::{
+    damage::{DamageType, Effectiveness},
+    Velocity,
+};
 
 pub struct DmgTextPlugin;
 
 impl Plugin for DmgTextPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (fade_text).run_if(in_state(AppState::Game)));
+        app.add_systems(
+            Update,
+            (fade_text, bounce_text).run_if(in_state(AppState::Game)),
+        );
     }
 }
 
@@ -24,6 +30,16 @@ impl DmgText {
     pub const FADING_DURATION: Duration = Duration::from_secs(1);
 }
 
+/// The text pops out and back while the timer runs
+#[derive(Component, Clone, Deref, DerefMut)]
+pub struct DmgTextBounce(pub Timer);
+
+impl DmgTextBounce {
+    pub const DURATION: Duration = Duration::from_millis(300);
+    /// Extra scale at the top of the bounce
+    pub const HEIGHT: f32 = 0.6;
+}
+
 pub fn fade_text(
     mut commands: Commands,
     time: Res<Time>,
@@ -45,23 +61,94 @@ pub fn fade_text(
     }
 }
 
-pub fn spawn_dmg_text(commands: &mut Commands, translation: Vec3, dmg: u32) {
-    commands.spawn((
-        DmgText {
-            timer: Timer::new(DmgText::FADING_DURATION, TimerMode::Once),
+fn bounce_text(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &mut DmgTextBounce)>,
+) {
+    for (entity, mut transform, mut bounce) in &mut query {
+        bounce.tick(time.delta());
+
+        let height = (bounce.fraction() * std::f32::consts::PI).sin() * DmgTextBounce::HEIGHT;
+        transform.scale = Vec3::splat(1.0 + height);
+
+        if bounce.finished() {
+            commands.entity(entity).remove::<DmgTextBounce>();
+        }
+    }
+}
+
+/// Damage number colored by its [DamageType]: effective hits are bigger and
+/// marked with `!`, resisted ones are smaller and between parentheses. Crits
+/// keep the color, are bigger and bounce.
+pub fn spawn_dmg_text(
+    commands: &mut Commands,
+    translation: Vec3,
+    dmg: u32,
+    damage_type: DamageType,
+    effectiveness: Effectiveness,
+    crit: bool,
+) {
+    let (mut value, mut font_size) = match effectiveness {
+        Effectiveness::Resisted => (format!("({dmg})"), 22.0),
+        Effectiveness::Normal => (dmg.to_string(), 30.0),
+        Effectiveness::Effective => (format!("{dmg}!"), 38.0),
+    };
+    if crit {
+        value = format!("CRIT {value}");
+        font_size += 10.0;
+    }
+
+    let text = spawn_floating_text(
+        commands,
+        translation,
+        value,
+        TextStyle {
+            font_size,
+            color: damage_type.color(),
+            ..default()
         },
-        Velocity(DmgText::SPEED),
-        Text2dBundle {
-            text: Text::from_section(
-                dmg.to_string(),
-                TextStyle {
-                    font_size: 30.0,
-                    color: Color::RED,
-                    ..default()
-                },
-            ),
-            transform: Transform::from_translation(translation),
+    );
+
+    if crit {
+        commands.entity(text).insert(DmgTextBounce(Timer::new(
+            DmgTextBounce::DURATION,
+            TimerMode::Once,
+        )));
+    }
+}
+
+/// Points gained by destroying a block
+pub fn spawn_score_popup(commands: &mut Commands, translation: Vec3, points: u32) {
+    spawn_floating_text(
+        commands,
+        translation,
+        format!("+{points}"),
+        TextStyle {
+            font_size: 24.0,
+            color: Color::GOLD,
             ..default()
         },
-    ));
+    );
+}
+
+fn spawn_floating_text(
+    commands: &mut Commands,
+    translation: Vec3,
+    value: String,
+    style: TextStyle,
+) -> Entity {
+    commands
+        .spawn((
+            DmgText {
+                timer: Timer::new(DmgText::FADING_DURATION, TimerMode::Once),
+            },
+            Velocity(DmgText::SPEED),
+            Text2dBundle {
+                text: Text::from_section(value, style),
+                transform: Transform::from_translation(translation),
+                ..default()
+            },
+        ))
+        .id()
 }
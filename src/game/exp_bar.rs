@@ -1,10 +1,14 @@
+use std::time::Duration;
+
 use bevy::{math::vec2, prelude::*, sprite::Anchor};
 
 use crate::{camera, AppState};
 
 use super::{
     level::Level,
-    paddle::{level_exp_cap, Paddle},
+    paddle::{Coop, LevelUp, Paddle, Player},
+    tuning::Tuning,
+    GameMode,
 };
 
 pub struct ExpBarPlugin;
@@ -13,25 +17,66 @@ impl Plugin for ExpBarPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(AppState::Game), spawn_exp_bar)
             .add_systems(OnExit(AppState::Game), cleanup_exp_bar)
-            .add_systems(Update, update_bar.run_if(in_state(AppState::Game)));
+            .add_systems(
+                Update,
+                (update_bar, update_bar_text, start_flash, flash_bar)
+                    .run_if(in_state(AppState::Game)),
+            );
     }
 }
 
+/// Exp progress of the paddle of the same [Player]
 #[derive(Component, Clone)]
 pub struct ExpBar {
     max_len: f32,
 }
 
+impl ExpBar {
+    pub const COLOR: Color = Color::RED;
+    pub const FLASH_COLOR: Color = Color::WHITE;
+    pub const FLASH_DURATION: Duration = Duration::from_millis(600);
+    pub const FONT_SIZE: f32 = 20.0;
+}
+
+/// "Lv N  exp/cap" written over the [ExpBar], prefixed by the [Player] in co-op
+#[derive(Component, Clone)]
+pub struct ExpBarText;
+
+/// The [ExpBar] fades from [ExpBar::FLASH_COLOR] back to [ExpBar::COLOR] after a level up
+#[derive(Component, Clone, Deref, DerefMut)]
+pub struct ExpBarFlash(pub Timer);
+
 #[derive(Resource, Clone)]
 pub struct ExpBarData {
-    bar: Entity,
+    bars: Vec<Entity>,
+}
+
+/// One bar per [Player], splitting the bottom of the window
+fn spawn_exp_bar(mut commands: Commands, mode: Res<GameMode>, coop: Res<Coop>) {
+    let players = mode.players(*coop);
+    let box_size = vec2(camera::WINDOW_SIZE.x / players.len() as f32, 40.0);
+
+    let bars = players
+        .iter()
+        .enumerate()
+        .map(|(i, &player)| {
+            let translation = camera::WINDOW_BOT_LEFT + vec2(i as f32 * box_size.x, 0.0);
+            spawn_player_exp_bar(&mut commands, player, box_size, translation)
+        })
+        .collect();
+
+    commands.insert_resource(ExpBarData { bars });
 }
 
-fn spawn_exp_bar(mut commands: Commands) {
-    let box_size = vec2(camera::WINDOW_SIZE.x, 40.0);
+fn spawn_player_exp_bar(
+    commands: &mut Commands,
+    player: Player,
+    box_size: Vec2,
+    translation: Vec2,
+) -> Entity {
     let padding = vec2(15.0, 15.0);
 
-    let id = commands
+    commands
         .spawn(SpriteBundle {
             sprite: Sprite {
                 color: Color::ORANGE,
@@ -39,7 +84,7 @@ fn spawn_exp_bar(mut commands: Commands) {
                 custom_size: Some(box_size),
                 ..default()
             },
-            transform: Transform::from_translation(camera::WINDOW_BOT_LEFT.extend(0.0)),
+            transform: Transform::from_translation(translation.extend(0.0)),
             ..default()
         })
         .with_children(|parent| {
@@ -58,9 +103,10 @@ fn spawn_exp_bar(mut commands: Commands) {
                 ExpBar {
                     max_len: (box_size - padding).x,
                 },
+                player,
                 SpriteBundle {
                     sprite: Sprite {
-                        color: Color::RED,
+                        color: ExpBar::COLOR,
                         anchor: Anchor::BottomLeft,
                         custom_size: Some(box_size - padding),
                         ..default()
@@ -69,25 +115,110 @@ fn spawn_exp_bar(mut commands: Commands) {
                     ..default()
                 },
             ));
-        })
-        .id();
 
-    commands.insert_resource(ExpBarData { bar: id });
+            parent.spawn((
+                ExpBarText,
+                player,
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font_size: ExpBar::FONT_SIZE,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ),
+                    transform: Transform::from_translation((0.5 * box_size).extend(3.0)),
+                    ..default()
+                },
+            ));
+        })
+        .id()
 }
 
 fn cleanup_exp_bar(mut commands: Commands, exp_bar_data: Res<ExpBarData>) {
-    commands.entity(exp_bar_data.bar).despawn_recursive();
+    for &bar in &exp_bar_data.bars {
+        commands.entity(bar).despawn_recursive();
+    }
 
     commands.remove_resource::<ExpBarData>();
 }
 
-fn update_bar(level_q: Query<&Level, With<Paddle>>, mut bar_q: Query<(&mut Sprite, &ExpBar)>) {
-    let level = level_q.single();
-    let (mut sprite, bar) = bar_q.single_mut();
+fn update_bar(
+    tuning: Res<Tuning>,
+    level_q: Query<(&Level, &Player), With<Paddle>>,
+    mut bar_q: Query<(&mut Sprite, &ExpBar, &Player)>,
+) {
+    for (level, player) in &level_q {
+        let Some((mut sprite, bar, _)) = bar_q.iter_mut().find(|(_, _, p)| *p == player) else {
+            continue;
+        };
+
+        let percentage = level.exp as f32 / tuning.level_exp_cap(level.level) as f32;
+
+        sprite.custom_size = sprite
+            .custom_size
+            .map(|size| vec2(percentage * bar.max_len, size.y));
+    }
+}
 
-    let percentage = level.exp as f32 / level_exp_cap(level.level) as f32;
+#[allow(clippy::type_complexity)]
+fn update_bar_text(
+    tuning: Res<Tuning>,
+    mode: Res<GameMode>,
+    coop: Res<Coop>,
+    level_q: Query<(&Level, &Player), (With<Paddle>, Changed<Level>)>,
+    mut text_q: Query<(&mut Text, &Player), With<ExpBarText>>,
+) {
+    for (level, player) in &level_q {
+        let prefix = if mode.players(*coop).len() > 1 {
+            format!("{}  ", player.name())
+        } else {
+            String::new()
+        };
 
-    sprite.custom_size = sprite
-        .custom_size
-        .map(|size| vec2(percentage * bar.max_len, size.y));
+        for (mut text, _) in text_q.iter_mut().filter(|(_, p)| *p == player) {
+            text.sections[0].value = format!(
+                "{prefix}Lv {}  {}/{}",
+                level.level,
+                level.exp,
+                tuning.level_exp_cap(level.level)
+            );
+        }
+    }
+}
+
+fn start_flash(
+    mut commands: Commands,
+    mut level_up: EventReader<LevelUp>,
+    bar_q: Query<(Entity, &Player), With<ExpBar>>,
+) {
+    for level_up in level_up.read() {
+        for (entity, _) in bar_q.iter().filter(|(_, p)| **p == level_up.player) {
+            commands.entity(entity).insert(ExpBarFlash(Timer::new(
+                ExpBar::FLASH_DURATION,
+                TimerMode::Once,
+            )));
+        }
+    }
+}
+
+fn flash_bar(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut bar_q: Query<(Entity, &mut Sprite, &mut ExpBarFlash)>,
+) {
+    for (entity, mut sprite, mut flash) in &mut bar_q {
+        // Real time, so that the flash is visible while the game is paused on level up
+        flash.tick(time.delta());
+
+        let t = flash.fraction();
+        let [r0, g0, b0, _] = ExpBar::FLASH_COLOR.as_rgba_f32();
+        let [r1, g1, b1, _] = ExpBar::COLOR.as_rgba_f32();
+        sprite.color = Color::rgb(r0 + (r1 - r0) * t, g0 + (g1 - g0) * t, b0 + (b1 - b0) * t);
+
+        if flash.finished() {
+            commands.entity(entity).remove::<ExpBarFlash>();
+        }
+    }
 }
@@ -0,0 +1,154 @@
+use bevy::{
+    math::{bounding::Aabb2d, *},
+    prelude::*,
+};
+
+use crate::AppState;
+
+use super::{
+    apply_velocity, cleanup_component, collide_volume,
+    paddle::{ExpUp, Paddle, Player},
+    Collider, MainBox, PlayerCollider, StartingBonuses, Velocity,
+};
+
+pub struct ExpOrbPlugin;
+
+impl Plugin for ExpOrbPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExpDrops>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    attract_exp_orbs.before(apply_velocity),
+                    (collect_exp_orbs, check_exp_orbs_out_of_bounds).after(apply_velocity),
+                )
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(OnExit(AppState::Game), cleanup_component::<ExpOrb>);
+    }
+}
+
+/// How destroyed blocks give their exp
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct ExpDrops {
+    /// Blocks drop [ExpOrb]s that must be caught by the paddle instead of giving
+    /// their exp right away
+    pub orbs: bool,
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ExpOrb {
+    pub exp: u32,
+    /// Velocity of the orb when no magnet pulls it
+    pub fall: Vec2,
+}
+
+impl ExpOrb {
+    pub const SIZE: Vec2 = vec2(10.0, 10.0);
+    pub const COLOR: Color = Color::LIME_GREEN;
+    pub const SPEED: Vec2 = vec2(0.0, -80.0);
+    /// Speed of the orbs pulled in by the magnet
+    pub const MAGNET_SPEED: f32 = 300.0;
+}
+
+#[derive(Bundle)]
+pub struct ExpOrbBundle {
+    pub sprite: SpriteBundle,
+    pub orb: ExpOrb,
+    pub collider: PlayerCollider,
+    pub velocity: Velocity,
+}
+
+impl ExpOrbBundle {
+    pub fn new(translation: Vec3, exp: u32) -> Self {
+        Self {
+            sprite: SpriteBundle {
+                transform: Transform::from_translation(translation),
+                sprite: Sprite {
+                    color: ExpOrb::COLOR,
+                    custom_size: Some(ExpOrb::SIZE),
+                    ..default()
+                },
+                ..default()
+            },
+            orb: ExpOrb {
+                exp,
+                fall: ExpOrb::SPEED,
+            },
+            collider: PlayerCollider(Collider { size: ExpOrb::SIZE }),
+            velocity: Velocity(ExpOrb::SPEED),
+        }
+    }
+
+    /// Fall towards the top edge if `edge` is positive, as given by
+    /// [Player::versus_edge], instead of the bottom one
+    pub fn falling_to(mut self, edge: f32) -> Self {
+        self.orb.fall.y = ExpOrb::SPEED.y.abs() * edge.signum();
+        self.velocity.0 = self.orb.fall;
+        self
+    }
+}
+
+/// Orbs close enough to the paddle fly towards it, the others keep falling
+fn attract_exp_orbs(
+    bonuses: Res<StartingBonuses>,
+    paddle_q: Query<&Transform, With<Paddle>>,
+    mut orb_q: Query<(&Transform, &mut Velocity, &ExpOrb)>,
+) {
+    if bonuses.magnet_radius <= 0.0 {
+        return;
+    }
+
+    for (transform, mut velocity, orb) in &mut orb_q {
+        // Pulled by the closest paddle
+        let closest = paddle_q
+            .iter()
+            .map(|paddle| paddle.translation.xy() - transform.translation.xy())
+            .min_by(|a, b| a.length().total_cmp(&b.length()));
+
+        **velocity = match closest.filter(|o| o.length() < bonuses.magnet_radius) {
+            Some(offset) => offset.normalize_or_zero() * ExpOrb::MAGNET_SPEED,
+            None => orb.fall,
+        };
+    }
+}
+
+fn collect_exp_orbs(
+    mut commands: Commands,
+    mut exp_up: EventWriter<ExpUp>,
+    paddle_q: Query<(&Transform, &Collider, &Player), With<Paddle>>,
+    orb_q: Query<(Entity, &Transform, &PlayerCollider, &ExpOrb)>,
+) {
+    for (entity, transform, collider, orb) in &orb_q {
+        // The exp goes to whoever catches the orb
+        let catcher = paddle_q.iter().find(|(paddle_t, paddle_c, _)| {
+            collide_volume(
+                Aabb2d::new(transform.translation.truncate(), collider.size / 2.),
+                Aabb2d::new(paddle_t.translation.truncate(), paddle_c.size / 2.),
+            )
+            .is_some()
+        });
+
+        if let Some((_, _, &player)) = catcher {
+            exp_up.send(ExpUp {
+                player,
+                exp: orb.exp,
+            });
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn check_exp_orbs_out_of_bounds(
+    mut commands: Commands,
+    main_box: Res<MainBox>,
+    query: Query<(Entity, &Transform), With<ExpOrb>>,
+) {
+    let max_y = 0.5 * main_box.size.y;
+
+    for (entity, transform) in &query {
+        if transform.translation.y.abs() > max_y {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -1,7 +1,77 @@
+use std::ops::Range;
+
 use bevy::prelude::*;
 
+use super::tuning::Tuning;
+
 #[derive(Component, Clone, Default)]
 pub struct Level {
     pub level: u32,
     pub exp: u32,
 }
+
+impl Level {
+    /// Add `exp` and return the levels reached, a single batch can cross
+    /// several thresholds
+    pub fn gain_exp(&mut self, exp: u32, tuning: &Tuning) -> Range<u32> {
+        let start = self.level + 1;
+        self.exp += exp;
+
+        loop {
+            let level_cap = tuning.level_exp_cap(self.level);
+            if self.exp < level_cap {
+                break;
+            }
+
+            self.exp -= level_cap;
+            self.level += 1;
+        }
+
+        start..self.level + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tuning() -> Tuning {
+        Tuning {
+            level_cap: 10,
+            level_up_multiplier: 1.0,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn exp_below_the_cap_does_not_level_up() {
+        let mut level = Level::default();
+        assert!(level.gain_exp(9, &tuning()).is_empty());
+        assert_eq!(level.level, 0);
+        assert_eq!(level.exp, 9);
+    }
+
+    #[test]
+    fn exact_cap_levels_up_once() {
+        let mut level = Level::default();
+        assert_eq!(level.gain_exp(10, &tuning()), 1..2);
+        assert_eq!(level.exp, 0);
+    }
+
+    #[test]
+    fn single_batch_crosses_several_levels() {
+        // Caps of 10, 20 and 30 exp
+        let mut level = Level::default();
+        assert_eq!(level.gain_exp(65, &tuning()), 1..4);
+        assert_eq!(level.level, 3);
+        assert_eq!(level.exp, 5);
+    }
+
+    #[test]
+    fn leftover_exp_counts_towards_the_next_level() {
+        let mut level = Level::default();
+        level.gain_exp(15, &tuning());
+        assert_eq!(level.gain_exp(15, &tuning()), 2..3);
+        assert_eq!(level.exp, 0);
+    }
+}
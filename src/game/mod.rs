@@ -8,28 +8,75 @@ use bevy::{
     prelude::*,
 };
 use powerup::*;
+use rand::Rng;
 use scoreboard::*;
 
-use self::ball::{Ball, BallBundle, BallCollision, BallEnlargmentTimer, BallPlugin};
-use self::block::{block_go_down, Block, BlockBundle, BlockPlugin};
+pub use self::difficulty::{AdaptiveDifficulty, Difficulty};
+pub use self::exp_orb::ExpDrops;
+pub use self::mode::GameMode;
+pub use self::mutator::{Mutator, Mutators};
+pub use self::paddle::Coop;
+pub use self::rng::{GameRng, ReplaySeed, RunSeed};
+pub use self::scoreboard::FinalScore;
+pub use self::skill::{Skill, SkillBranch, SkillTree};
+pub use self::stats::RunStats;
+pub use self::tuning::Tuning;
+
+use self::ball::{
+    Ball, BallBundle, BallCollision, BallEnlargmentTimer, BallLost, BallPlugin, LastPaddle,
+};
+use self::block::{
+    block_go_down, Block, BlockBundle, BlockDamaged, BlockDestroyed, BlockHit, BlockPlugin,
+};
+use self::boss::{Boss, BossCell, BossDefeated, BossPlugin};
+use self::damage::{scaled_damage, DamageRoll, DamageType, Effectiveness};
+use self::difficulty::DifficultyPlugin;
 use self::dmg_text::{spawn_dmg_text, DmgTextPlugin};
 use self::exp_bar::*;
-use self::paddle::{ExpUp, Paddle, PaddleBundle, PaddleEnlargedTimer, PaddlePlugin};
+use self::exp_orb::{ExpOrbBundle, ExpOrbPlugin};
+use self::mode::{spawn_level, ModePlugin};
+use self::mutator::MutatorPlugin;
+use self::obstacle::ObstaclePlugin;
+use self::paddle::{
+    ExpUp, Paddle, PaddleBundle, PaddleDeflection, PaddleHit, PaddlePlugin, PaddleResizeTimer,
+    PaddleVelocity, Player,
+};
+use self::rng::{setup_rng, RngPlugin};
+use self::scoring::ScoringPlugin;
+use self::skill::{Modifiers, SkillPlugin, Stat};
+use self::stats::StatsPlugin;
+use self::status::{Frozen, StatusPlugin};
+use self::tuning::TuningPlugin;
+use self::versus::{is_versus, VersusPlugin, WallSide};
 
 mod ball;
 mod block;
+mod boss;
+mod damage;
+mod difficulty;
 mod dmg_text;
 mod exp_bar;
+mod exp_orb;
 mod level;
+mod mode;
+mod mutator;
+mod obstacle;
 mod paddle;
 mod powerup;
+mod rng;
 mod scoreboard;
+mod scoring;
+mod skill;
+mod stats;
+mod status;
+mod tuning;
+mod versus;
 
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::Game), setup_game)
+        app.add_systems(OnEnter(AppState::Game), setup_game.after(setup_rng))
             .add_plugins((
                 PowerupPlugin,
                 BallPlugin,
@@ -38,13 +85,34 @@ impl Plugin for GamePlugin {
                 DmgTextPlugin,
                 ScoreboardPlugin,
                 ExpBarPlugin,
+                ScoringPlugin,
+                StatsPlugin,
+                ObstaclePlugin,
+                BossPlugin,
+                SkillPlugin,
+                ExpOrbPlugin,
+                StatusPlugin,
+            ))
+            .add_plugins((
+                RngPlugin,
+                TuningPlugin,
+                DifficultyPlugin,
+                ModePlugin,
+                MutatorPlugin,
+                VersusPlugin,
             ))
             .init_state::<GameState>()
+            .init_resource::<StartingBonuses>()
+            .init_resource::<DamageRoll>()
             .add_systems(Update, (bevy::window::close_on_esc,))
+            .add_systems(
+                Update,
+                apply_attack_modifiers.run_if(in_state(AppState::Game)),
+            )
             .add_systems(
                 FixedUpdate,
                 (
-                    block_go_down,
+                    block_go_down.run_if(mode_descends),
                     apply_velocity,
                     (
                         check_ball_collision,
@@ -53,7 +121,10 @@ impl Plugin for GamePlugin {
                     )
                         .after(apply_velocity)
                         .after(block_go_down),
-                    check_game_over.after(check_ball_out_of_bound),
+                    damage_blocks.after(check_ball_collision),
+                    check_game_over
+                        .after(check_ball_out_of_bound)
+                        .run_if(not(is_versus)),
                 )
                     .run_if(in_state(AppState::Game)),
             )
@@ -72,9 +143,10 @@ impl Plugin for GamePlugin {
 }
 
 #[derive(States, Default, Clone, Debug, PartialEq, Eq, Hash)]
-enum GameState {
+pub enum GameState {
     #[default]
     Looping,
+    /// The game is paused while a skill point is spent
     LevelingUp,
 }
 
@@ -83,6 +155,21 @@ const WALL_WIDTH: f32 = 1200.0;
 const WALL_HEIGHT: f32 = 600.0;
 const WALL_THICKNESS: f32 = 30.0;
 
+/// Bonuses every run starts with, earned outside of the game
+#[derive(Resource, Default, Debug, Clone, Copy)]
+pub struct StartingBonuses {
+    pub attack: u32,
+    pub lives: u32,
+    pub paddle_width: f32,
+    pub exp: u32,
+    /// Distance from the paddle where exp orbs are pulled in
+    pub magnet_radius: f32,
+}
+
+/// Balls that can still be respawned before the game is over
+#[derive(Resource, Clone, Copy, Deref, DerefMut)]
+pub struct Lives(pub u32);
+
 #[derive(Component, Clone, Deref, DerefMut)]
 pub struct Velocity(pub Vec2);
 
@@ -97,9 +184,6 @@ pub struct Collider {
     pub size: Vec2,
 }
 
-#[derive(Component)]
-struct GameEntities;
-
 #[derive(Component, Deref, DerefMut)]
 pub struct PlayerCollider(pub Collider);
 
@@ -113,6 +197,27 @@ struct WallBundle {
     wall: Wall,
 }
 
+impl WallBundle {
+    fn new(translation: Vec2, size: Vec2) -> Self {
+        Self {
+            sprite: SpriteBundle {
+                transform: Transform {
+                    translation: translation.extend(0.0),
+                    ..default()
+                },
+                sprite: Sprite {
+                    color: Color::SILVER,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                ..default()
+            },
+            collider: Collider { size },
+            wall: Wall,
+        }
+    }
+}
+
 /// Box where the game is enclosed
 #[derive(Resource, Clone, Copy)]
 pub struct MainBox {
@@ -127,9 +232,32 @@ pub enum Collision {
     Bottom,
 }
 
-fn setup_game(mut commands: Commands) {
-    // Paddle
-    commands.spawn(PaddleBundle::default());
+fn setup_game(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    bonuses: Res<StartingBonuses>,
+    modifiers: Res<Modifiers>,
+    coop: Res<Coop>,
+    mut rng: ResMut<GameRng>,
+    mut exp_up: EventWriter<ExpUp>,
+) {
+    // Paddles, side by side in co-op and facing each other in versus
+    let players = mode.players(*coop);
+    for (i, &player) in players.iter().enumerate() {
+        let paddle = PaddleBundle::with_size(Paddle::SIZE + vec2(bonuses.paddle_width, 0.0));
+        if mode.is_versus() {
+            commands.spawn(paddle.defending(player));
+        } else {
+            let x = (i as f32 - 0.5 * (players.len() - 1) as f32) * Paddle::COOP_SPACING;
+            commands.spawn(paddle.with_player(player, x));
+        }
+        exp_up.send(ExpUp {
+            player,
+            exp: bonuses.exp,
+        });
+    }
+    let lives = mode.lives().unwrap_or(bonuses.lives) as f32;
+    commands.insert_resource(Lives(modifiers.apply(Stat::Lives, lives).round() as u32));
 
     // Ball
     commands.spawn(BallBundle::default());
@@ -156,58 +284,57 @@ fn setup_game(mut commands: Commands) {
         ),
     ];
 
-    for wall in walls {
-        commands.spawn(WallBundle {
-            sprite: SpriteBundle {
-                transform: Transform {
-                    translation: wall.1.extend(0.0),
-                    ..default()
-                },
-                sprite: Sprite {
-                    color: Color::SILVER,
-                    custom_size: Some(wall.0),
-                    ..default()
-                },
-                ..default()
-            },
-            collider: Collider { size: wall.0 },
-            wall: Wall,
-        });
+    // The top and bottom are left open in versus, each is defended by a player
+    let walls = if mode.is_versus() {
+        &walls[2..]
+    } else {
+        &walls[..]
+    };
+    for &(size, translation) in walls {
+        commands.spawn(WallBundle::new(translation, size));
     }
 
     let main_box = MainBox {
-        size: vec2(
-            WALL_WIDTH as f32 - WALL_THICKNESS,
-            WALL_HEIGHT as f32 - WALL_THICKNESS,
-        ),
+        size: vec2(WALL_WIDTH - WALL_THICKNESS, WALL_HEIGHT - WALL_THICKNESS),
     };
     commands.insert_resource(main_box);
 
     // Blocks
-    spawn_blocks(&mut commands, main_box);
+    spawn_level(&mut commands, main_box, *mode, 0, &mut rng.field);
 }
 
-fn spawn_blocks(commands: &mut Commands, main_box: MainBox) {
-    for w in 0..Block::WIDTH {
-        for h in (0..Block::HEIGHT).step_by(2) {
-            let mut pos = vec3(
-                w as f32 * (Block::SIZE.x + Block::PADDING),
-                -h as f32 * (Block::SIZE.y + Block::PADDING),
-                0.0,
-            );
+fn mode_descends(mode: Res<GameMode>) -> bool {
+    mode.descends()
+}
 
-            let mut wall_top_right = 0.5 * vec3(-main_box.size.x, main_box.size.y, 0.0);
+/// Position of the block in column `col` and row `row`, counted from the top left
+fn block_translation(main_box: MainBox, col: i32, row: i32) -> Vec3 {
+    let pos = vec3(
+        col as f32 * (Block::SIZE.x + Block::PADDING),
+        -row as f32 * (Block::SIZE.y + Block::PADDING),
+        0.0,
+    );
 
-            // Add a half of a block size
-            wall_top_right += vec3(
-                Block::SIZE.x * 0.5 + Block::PADDING,
-                -(Block::SIZE.y * 0.5 + Block::PADDING),
-                0.0,
-            );
+    let mut wall_top_right = 0.5 * vec3(-main_box.size.x, main_box.size.y, 0.0);
 
-            pos += wall_top_right;
+    // Add a half of a block size
+    wall_top_right += vec3(
+        Block::SIZE.x * 0.5 + Block::PADDING,
+        -(Block::SIZE.y * 0.5 + Block::PADDING),
+        0.0,
+    );
 
-            commands.spawn(BlockBundle::from_translation(pos));
+    pos + wall_top_right
+}
+
+/// Random blocks on every other row
+fn spawn_blocks(commands: &mut Commands, main_box: MainBox, rng: &mut impl Rng) {
+    for w in 0..Block::WIDTH {
+        for h in (0..Block::HEIGHT).step_by(2) {
+            commands.spawn(BlockBundle::from_translation(
+                block_translation(main_box, w, h),
+                rng,
+            ));
         }
     }
 }
@@ -220,101 +347,249 @@ fn apply_velocity(mut query: Query<(&Velocity, &mut Transform)>, time: Res<Time>
     }
 }
 
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn check_ball_collision(
-    mut balls: Query<(&Transform, &mut Velocity, &Attack, &Ball)>,
-    mut colliders: Query<(
+    mut balls: Query<(
         Entity,
         &Transform,
-        &Collider,
-        Option<&mut Health>,
-        Option<&Block>,
-        Option<&Paddle>,
+        &mut Velocity,
+        &Attack,
+        &DamageType,
+        &Ball,
+        &mut LastPaddle,
     )>,
+    colliders: Query<
+        (
+            Entity,
+            &Transform,
+            &Collider,
+            Option<&Health>,
+            Option<&Block>,
+            Option<(&PaddleVelocity, &Player)>,
+        ),
+        Without<BossCell>,
+    >,
     collision_sound: Res<BallCollision>,
-    mut scoreboard: ResMut<Scoreboard>,
+    deflection: Res<PaddleDeflection>,
+    damage_roll: Res<DamageRoll>,
+    modifiers: Res<Modifiers>,
+    mut rng: ResMut<GameRng>,
     mut commands: Commands,
-    mut exp_up: EventWriter<ExpUp>,
+    mut block_damaged: EventWriter<BlockDamaged>,
+    mut paddle_hit: EventWriter<PaddleHit>,
 ) {
-    let mut tot_exp = 0;
-
-    for (entity, transform, collider, mut health, block, paddle) in &mut colliders {
-        for (ball_t, mut ball_v, attack, ball) in &mut balls {
+    for (entity, transform, collider, health, block, paddle) in &colliders {
+        for (ball_entity, ball_t, mut ball_v, attack, damage_type, ball, mut last_paddle) in
+            &mut balls
+        {
             let collision = collide_volume(
-                BoundingCircle::new(ball_t.translation.truncate(), ball.size.x / 2.),
+                BoundingCircle::new(ball_t.translation.truncate(), ball.radius()),
                 Aabb2d::new(transform.translation.truncate(), collider.size / 2.),
             );
 
             let Some(collision) = collision else { continue };
 
-            let mut reflect_x = false;
-            let mut reflect_y = false;
-            match collision {
-                Collision::Bottom => reflect_y = ball_v.y > 0.0,
-                Collision::Top => reflect_y = ball_v.y < 0.0,
-                Collision::Right => reflect_x = ball_v.x < 0.0,
-                Collision::Left => reflect_x = ball_v.x > 0.0,
-            }
-
             // Play bounce sound
             commands.spawn(AudioBundle {
                 source: collision_sound.clone(),
                 settings: PlaybackSettings::DESPAWN,
             });
 
-            if paddle.is_some() {
-                let dir = ball_t.translation - transform.translation;
-                ball_v.0 = dir.xy().normalize() * ball_v.length();
+            if let Some((paddle_velocity, &player)) = paddle {
+                **last_paddle = Some(player);
+                ball_v.0 = deflection.bounce(
+                    ball_t.translation.x - transform.translation.x,
+                    collider.size.x,
+                    **paddle_velocity,
+                    ball_v.length(),
+                );
+                // Paddles above the center send the balls back down
+                if transform.translation.y > 0.0 {
+                    ball_v.y = -ball_v.y;
+                }
+                paddle_hit.send(PaddleHit);
                 break;
             }
 
-            if reflect_x {
-                ball_v.x = -ball_v.x;
-            }
-            if reflect_y {
-                ball_v.y = -ball_v.y;
-            }
+            reflect_velocity(collision, &mut ball_v);
 
-            if block.is_none() {
+            let (Some(health), Some(block)) = (health, block) else {
                 break;
-            }
-
-            /* If the health is not zero continue with the ball iteration */
-            let Some(ref mut health) = health else {
-                unreachable!()
             };
 
-            let text_translation =
-                transform.translation + 0.5 * (ball_t.translation - transform.translation);
-            spawn_dmg_text(&mut commands, text_translation, **attack);
+            let (rolled, crit) = damage_roll.roll(**attack, &modifiers, &mut rng.combat);
+            let (damage, effectiveness) =
+                scaled_damage(rolled, block.kind.damage_multiplier(*damage_type));
+            block_damaged.send(BlockDamaged {
+                block: entity,
+                ball: Some(ball_entity),
+                player: **last_paddle,
+                damage,
+                crit,
+                damage_type: *damage_type,
+                effectiveness,
+                translation: transform.translation
+                    + 0.5 * (ball_t.translation - transform.translation),
+            });
 
-            if ***health > **attack {
-                ***health -= **attack;
+            /* If the block survives continue with the ball iteration */
+            if **health > damage {
                 continue;
             }
 
-            tot_exp += 1;
-            **scoreboard += 1;
-            commands.entity(entity).despawn();
-            Powerup::spawn_powerup(&mut commands, ball_t.translation);
-
             break;
         }
     }
+}
+
+/// Apply every [BlockDamaged], from ball hits and from status effects alike:
+/// show the damage, and destroy the blocks that run out of [Health]. The
+/// bosses take their damage here too and send a [BossDefeated].
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+fn damage_blocks(
+    mut commands: Commands,
+    mut block_damaged: EventReader<BlockDamaged>,
+    mut blocks: Query<
+        (&mut Health, &Transform, Option<&Block>, Option<&WallSide>),
+        Or<(With<Block>, With<Boss>)>,
+    >,
+    modifiers: Res<Modifiers>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    adaptive: Res<AdaptiveDifficulty>,
+    exp_drops: Res<ExpDrops>,
+    mut rng: ResMut<GameRng>,
+    mut exp_up: EventWriter<ExpUp>,
+    mut block_hit: EventWriter<BlockHit>,
+    mut block_destroyed: EventWriter<BlockDestroyed>,
+    mut boss_defeated: EventWriter<BossDefeated>,
+) {
+    let mut tot_exp = [0; Player::ALL.len()];
+
+    for damaged in block_damaged.read() {
+        let Ok((mut health, transform, block, wall_side)) = blocks.get_mut(damaged.block) else {
+            continue;
+        };
+        // Already destroyed by a previous event of this batch
+        if **health == 0 {
+            continue;
+        }
+
+        spawn_dmg_text(
+            &mut commands,
+            damaged.translation,
+            damaged.damage,
+            damaged.damage_type,
+            damaged.effectiveness,
+            damaged.crit,
+        );
+        if damaged.ball.is_some() {
+            block_hit.send(BlockHit);
+        }
+
+        if **health > damaged.damage {
+            **health -= damaged.damage;
+            continue;
+        }
+        **health = 0;
+
+        // Balls no paddle touched yet credit the opponent of the wall owner in
+        // versus, and the first player otherwise
+        let player = damaged
+            .player
+            .or_else(|| wall_side.map(|side| side.opponent()))
+            .unwrap_or_default();
+
+        let Some(block) = block else {
+            boss_defeated.send(BossDefeated {
+                boss: damaged.block,
+                player,
+                translation: transform.translation,
+            });
+            continue;
+        };
+
+        if exp_drops.orbs {
+            let orb = ExpOrbBundle::new(transform.translation, 1);
+            commands.spawn(if mode.is_versus() {
+                orb.falling_to(player.versus_edge())
+            } else {
+                orb
+            });
+        } else {
+            tot_exp[player.index()] += 1;
+        }
+        block_destroyed.send(BlockDestroyed {
+            ball: damaged.ball,
+            player,
+            kind: block.kind,
+            translation: transform.translation,
+        });
+        commands.entity(damaged.block).despawn();
+        if !mode.powerups() {
+            continue;
+        }
+        Powerup::spawn_powerup(
+            &mut commands,
+            damaged.translation,
+            modifiers.apply(Stat::PowerupChance, 1.0)
+                * difficulty.drop_factor()
+                * adaptive.drop_factor,
+            &mut rng.drops,
+        );
+    }
+
+    for player in Player::ALL {
+        if tot_exp[player.index()] > 0 {
+            exp_up.send(ExpUp {
+                player,
+                exp: tot_exp[player.index()],
+            });
+        }
+    }
+}
+
+/// Bounce `velocity` off the side that was hit, if it is moving into it
+fn reflect_velocity(collision: Collision, velocity: &mut Vec2) {
+    match collision {
+        Collision::Bottom if velocity.y > 0.0 => velocity.y = -velocity.y,
+        Collision::Top if velocity.y < 0.0 => velocity.y = -velocity.y,
+        Collision::Right if velocity.x < 0.0 => velocity.x = -velocity.x,
+        Collision::Left if velocity.x > 0.0 => velocity.x = -velocity.x,
+        _ => {}
+    }
+}
 
-    exp_up.send(ExpUp(tot_exp));
+/// Bounding volumes that can be the `other` side of [collide_volume]
+trait ClosestPoint: BoundingVolume {
+    fn closest_point_to(&self, point: Vec2) -> Vec2;
+}
+
+impl ClosestPoint for Aabb2d {
+    fn closest_point_to(&self, point: Vec2) -> Vec2 {
+        self.closest_point(point)
+    }
+}
+
+impl ClosestPoint for BoundingCircle {
+    fn closest_point_to(&self, point: Vec2) -> Vec2 {
+        self.closest_point(point)
+    }
 }
 
 /// Returns `Some` if `volume` collides with `other`. The returned `Collision` is the
 /// side of `volume` that `other` hit.
-fn collide_volume<V: IntersectsVolume<Aabb2d> + BoundingVolume<Position = Vec2>>(
-    volume: V,
-    other: Aabb2d,
-) -> Option<Collision> {
+fn collide_volume<V, O>(volume: V, other: O) -> Option<Collision>
+where
+    V: IntersectsVolume<O> + BoundingVolume<Position = Vec2>,
+    O: ClosestPoint,
+{
     if !volume.intersects(&other) {
         return None;
     }
 
-    let closest = other.closest_point(volume.center());
+    let closest = other.closest_point_to(volume.center());
     let offset = volume.center() - closest;
     let side = if offset.x.abs() > offset.y.abs() {
         if offset.x < 0. {
@@ -333,76 +608,186 @@ fn collide_volume<V: IntersectsVolume<Aabb2d> + BoundingVolume<Position = Vec2>>
 
 fn duplicate_balls(
     commands: &mut Commands,
-    query_ball: &Query<(&Velocity, &Transform, &mut Ball, &mut Sprite), Without<Paddle>>,
+    query_ball: &Query<
+        (
+            &Velocity,
+            &Transform,
+            &mut Ball,
+            &mut Sprite,
+            &mut DamageType,
+        ),
+        Without<Paddle>,
+    >,
+    main_box: &MainBox,
 ) {
     let balls = query_ball
         .into_iter()
-        .map(|(v, t, _, _)| BallBundle::from_trans_vel(*t, Velocity(-v.0)))
+        .map(|(v, t, ball, _, damage_type)| {
+            let velocity = Velocity(-v.0);
+            let transform =
+                t.with_translation(ball.spawn_translation(t.translation, velocity.0, main_box));
+            BallBundle::from_trans_vel(transform, velocity).with_damage_type(*damage_type)
+        })
         .collect::<Vec<_>>();
 
     commands.spawn_batch(balls);
 }
 
-fn enlarge_paddle(commands: &mut Commands, sprite: &mut Sprite, collider: &mut Collider) {
-    sprite.custom_size = Some(Paddle::ENLARGED_SIZE);
-    collider.size = Paddle::ENLARGED_SIZE;
+fn enlarge_paddle(
+    commands: &mut Commands,
+    entity: Entity,
+    paddle: &Paddle,
+    sprite: &mut Sprite,
+    collider: &mut Collider,
+    duration: f32,
+) {
+    let size = paddle.resized(Paddle::ENLARGE_FACTOR);
+    sprite.custom_size = Some(size);
+    collider.size = size;
 
-    commands.insert_resource(PaddleEnlargedTimer::default());
+    commands
+        .entity(entity)
+        .insert(PaddleResizeTimer::from_secs(duration));
 }
 
 fn enlarge_balls(
     commands: &mut Commands,
-    query_ball: &mut Query<(&Velocity, &Transform, &mut Ball, &mut Sprite), Without<Paddle>>,
+    query_ball: &mut Query<
+        (
+            &Velocity,
+            &Transform,
+            &mut Ball,
+            &mut Sprite,
+            &mut DamageType,
+        ),
+        Without<Paddle>,
+    >,
+    duration: f32,
 ) {
-    for (_, _, mut collider, mut sprite) in query_ball {
+    for (_, _, mut collider, mut sprite, _) in query_ball {
         sprite.custom_size = Some(Ball::ENLARGED_SIZE);
         collider.size = Ball::ENLARGED_SIZE;
     }
 
-    commands.insert_resource(BallEnlargmentTimer::default());
+    commands.insert_resource(BallEnlargmentTimer::from_secs(duration));
+}
+
+/// Every ball deals `damage_type` damage until it is lost
+fn imbue_balls(
+    query_ball: &mut Query<
+        (
+            &Velocity,
+            &Transform,
+            &mut Ball,
+            &mut Sprite,
+            &mut DamageType,
+        ),
+        Without<Paddle>,
+    >,
+    damage_type: DamageType,
+) {
+    for (_, _, _, mut sprite, mut ball_damage_type) in query_ball {
+        *ball_damage_type = damage_type;
+        sprite.color = damage_type.ball_color();
+    }
 }
 
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn check_powerups_collision(
     mut commands: Commands,
+    mut collected: EventWriter<PowerupCollected>,
+    main_box: Res<MainBox>,
+    modifiers: Res<Modifiers>,
+    tuning: Res<Tuning>,
     query_powerup: Query<(Entity, &Transform, &PlayerCollider, &Powerup)>,
-    mut query_paddle: Query<(&Transform, &mut Collider, &mut Sprite), With<Paddle>>,
-    mut query_ball: Query<(&Velocity, &Transform, &mut Ball, &mut Sprite), Without<Paddle>>,
+    mut query_paddle: Query<(Entity, &Transform, &mut Collider, &mut Sprite, &Paddle)>,
+    mut query_ball: Query<
+        (
+            &Velocity,
+            &Transform,
+            &mut Ball,
+            &mut Sprite,
+            &mut DamageType,
+        ),
+        Without<Paddle>,
+    >,
 ) {
-    let (paddle_transform, mut paddle_collider, mut paddle_sprite) = query_paddle.single_mut();
-
     for (entity, transform, collider, powerup) in &query_powerup {
-        let collision = collide_volume(
-            Aabb2d::new(transform.translation.truncate(), collider.size / 2.),
-            Aabb2d::new(
-                paddle_transform.translation.truncate(),
-                paddle_collider.size / 2.,
-            ),
-        );
-
-        if collision.is_none() {
+        // Whoever catches the powerup gets it
+        let catcher = query_paddle
+            .iter_mut()
+            .find(|(_, paddle_t, paddle_c, _, _)| {
+                collide_volume(
+                    Aabb2d::new(transform.translation.truncate(), collider.size / 2.),
+                    Aabb2d::new(paddle_t.translation.truncate(), paddle_c.size / 2.),
+                )
+                .is_some()
+            });
+        let Some((paddle_entity, _, mut paddle_collider, mut paddle_sprite, paddle)) = catcher
+        else {
             continue;
-        }
+        };
 
         match powerup.class {
-            PowerupClass::DuplicateBall => duplicate_balls(&mut commands, &query_ball),
-            PowerupClass::EnlargeBall => enlarge_balls(&mut commands, &mut query_ball),
-            PowerupClass::EnlargePaddle => {
-                enlarge_paddle(&mut commands, &mut paddle_sprite, &mut paddle_collider)
-            }
+            PowerupClass::DuplicateBall => duplicate_balls(&mut commands, &query_ball, &main_box),
+            PowerupClass::EnlargeBall => enlarge_balls(
+                &mut commands,
+                &mut query_ball,
+                modifiers.apply(Stat::PowerupDuration, tuning.ball_enlarge_timeout),
+            ),
+            PowerupClass::EnlargePaddle => enlarge_paddle(
+                &mut commands,
+                paddle_entity,
+                paddle,
+                &mut paddle_sprite,
+                &mut paddle_collider,
+                modifiers.apply(Stat::PowerupDuration, tuning.paddle_resize_timeout),
+            ),
+            PowerupClass::FireBall => imbue_balls(&mut query_ball, DamageType::Fire),
+            PowerupClass::IceBall => imbue_balls(&mut query_ball, DamageType::Ice),
+            PowerupClass::PoisonBall => imbue_balls(&mut query_ball, DamageType::Poison),
         }
 
+        collected.send(PowerupCollected(powerup.class));
         commands.entity(entity).despawn();
     }
 }
 
-fn check_game_over(mut state: ResMut<NextState<AppState>>, query: Query<(), With<Ball>>) {
+fn check_game_over(
+    mut commands: Commands,
+    mut state: ResMut<NextState<AppState>>,
+    mut lives: ResMut<Lives>,
+    query: Query<(), With<Ball>>,
+) {
     if !query.is_empty() {
         return;
     }
 
+    if **lives > 0 {
+        **lives -= 1;
+        commands.spawn(BallBundle::default());
+        return;
+    }
+
     state.set(AppState::GameOver);
 }
 
+/// Give the [StartingBonuses] attack and the [Stat::Attack] modifiers to every ball
+fn apply_attack_modifiers(
+    bonuses: Res<StartingBonuses>,
+    modifiers: Res<Modifiers>,
+    mut query: Query<(&mut Attack, Ref<Ball>)>,
+) {
+    let base = (Ball::ATTACK + bonuses.attack) as f32;
+    let attack = modifiers.apply(Stat::Attack, base).round() as u32;
+
+    for (mut ball_attack, ball) in &mut query {
+        if ball.is_added() || modifiers.is_changed() {
+            **ball_attack = attack;
+        }
+    }
+}
+
 fn cleanup_component<C>(mut commands: Commands, query: Query<Entity, With<C>>)
 where
     C: Component,
@@ -415,11 +800,22 @@ where
 fn check_ball_out_of_bound(
     mut commands: Commands,
     main_box: Res<MainBox>,
+    mode: Res<GameMode>,
     query: Query<(Entity, &Transform, &Ball)>,
+    mut ball_lost: EventWriter<BallLost>,
 ) {
     for (entity, Transform { translation, .. }, Ball { size }) in &query {
-        if translation.y - 0.5 * size.y < -0.5 * main_box.size.y {
-            commands.entity(entity).despawn();
+        let bottom = translation.y - 0.5 * size.y < -0.5 * main_box.size.y;
+        let top = mode.is_versus() && translation.y + 0.5 * size.y > 0.5 * main_box.size.y;
+        if !bottom && !top {
+            continue;
         }
+
+        commands.entity(entity).despawn();
+        ball_lost.send(BallLost {
+            defender: mode
+                .is_versus()
+                .then_some(if top { Player::Two } else { Player::One }),
+        });
     }
 }
@@ -0,0 +1,535 @@
+use std::time::Duration;
+
+use bevy::{math::vec2, prelude::*, sprite::Anchor};
+use rand::Rng;
+
+use crate::{camera, AppState};
+
+use super::{
+    block::{block_go_down, Block, BlockBundle, BlockKind},
+    block_translation, damage_blocks,
+    obstacle::{Bumper, Obstacle, Portal},
+    paddle::{Coop, Paddle, Player},
+    rng::GameRng,
+    scoreboard::Scoreboard,
+    scoring::Combo,
+    spawn_blocks,
+    versus::spawn_versus_walls,
+    Lives, MainBox,
+};
+
+pub struct ModePlugin;
+
+impl Plugin for ModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameMode>()
+            .add_systems(OnEnter(AppState::Game), setup_mode)
+            .add_systems(Update, update_mode_text.run_if(in_state(AppState::Game)))
+            .add_systems(
+                FixedUpdate,
+                (
+                    tick_time_limit,
+                    check_field_cleared.after(damage_blocks),
+                    check_blocks_reached_paddle.after(block_go_down),
+                )
+                    .run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+/// Ruleset of the current run, picked from the main menu
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameMode {
+    /// Fixed levels cleared one after the other
+    Classic,
+    /// Rows keep coming down until the blocks reach the paddle
+    #[default]
+    Endless,
+    /// Clear as many blocks as possible before the time runs out
+    TimeAttack,
+    /// A single fixed level with a few balls and no descent
+    Puzzle,
+    /// Two players on opposite edges, each behind their own block wall
+    Versus,
+}
+
+impl GameMode {
+    /// Modes against the field rather than another player, the only ones with
+    /// high scores and daily challenges
+    pub const SOLO: [GameMode; 4] = [
+        GameMode::Classic,
+        GameMode::Endless,
+        GameMode::TimeAttack,
+        GameMode::Puzzle,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Endless => "Endless",
+            GameMode::TimeAttack => "Time attack",
+            GameMode::Puzzle => "Puzzle",
+            GameMode::Versus => "Versus",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            GameMode::Classic => GameMode::Endless,
+            GameMode::Endless => GameMode::TimeAttack,
+            GameMode::TimeAttack => GameMode::Puzzle,
+            GameMode::Puzzle => GameMode::Versus,
+            GameMode::Versus => GameMode::Classic,
+        }
+    }
+
+    pub fn is_versus(self) -> bool {
+        self == GameMode::Versus
+    }
+
+    /// [Player]s in the next runs, versus is always played by two
+    pub fn players(self, coop: Coop) -> &'static [Player] {
+        if self.is_versus() {
+            &Player::ALL
+        } else {
+            coop.players()
+        }
+    }
+
+    /// Returns `false` if blocks never drop powerups, they only fall towards
+    /// the bottom paddle
+    pub fn powerups(self) -> bool {
+        !self.is_versus()
+    }
+
+    /// Returns `true` if the blocks go down and new rows are spawned
+    pub fn descends(self) -> bool {
+        matches!(self, GameMode::Endless | GameMode::TimeAttack)
+    }
+
+    /// The run is won when this runs out
+    pub fn time_limit(self) -> Option<Duration> {
+        match self {
+            GameMode::TimeAttack => Some(Duration::from_secs(90)),
+            _ => None,
+        }
+    }
+
+    /// Balls left after the first one, `None` uses the [StartingBonuses](super::StartingBonuses)
+    pub fn lives(self) -> Option<u32> {
+        match self {
+            GameMode::Puzzle => Some(2),
+            _ => None,
+        }
+    }
+
+    /// Fixed block patterns played in order, empty for a random field
+    pub fn levels(self) -> &'static [Level] {
+        match self {
+            GameMode::Classic => &CLASSIC_LEVELS,
+            GameMode::Puzzle => &PUZZLE_LEVELS,
+            GameMode::Endless | GameMode::TimeAttack | GameMode::Versus => &[],
+        }
+    }
+
+    /// What follows the clearing of the field of `level`, `None` if the field
+    /// is never cleared for good
+    pub fn field_cleared(self, level: usize) -> Option<FieldCleared> {
+        match self {
+            // Versus walls keep getting garbage rows
+            GameMode::Endless | GameMode::Versus => None,
+            GameMode::Classic if level + 1 < self.levels().len() => {
+                Some(FieldCleared::NextLevel(level + 1))
+            }
+            // The field is refilled until the time runs out
+            GameMode::TimeAttack => Some(FieldCleared::NextLevel(0)),
+            GameMode::Classic | GameMode::Puzzle => Some(FieldCleared::Won),
+        }
+    }
+
+    /// Score added for clearing the field with `lives` balls left
+    pub fn clear_score(self, lives: u32) -> u32 {
+        match self {
+            // Every ball left unused is worth a clear bonus
+            GameMode::Puzzle => (1 + lives) * self.clear_bonus(),
+            _ => self.clear_bonus(),
+        }
+    }
+
+    /// Score added every time the field is cleared
+    pub fn clear_bonus(self) -> u32 {
+        match self {
+            GameMode::Classic => 500,
+            GameMode::Endless => 0,
+            GameMode::TimeAttack => 300,
+            GameMode::Puzzle => 1000,
+            GameMode::Versus => 0,
+        }
+    }
+
+    /// Obstacles spawned inside the walls when a run starts
+    pub fn arena(self) -> Vec<Obstacle> {
+        match self {
+            GameMode::Classic | GameMode::Versus => Vec::new(),
+            GameMode::Endless => vec![
+                Obstacle::Bumper {
+                    translation: vec2(-300.0, -80.0),
+                    radius: Bumper::RADIUS,
+                },
+                Obstacle::Bumper {
+                    translation: vec2(300.0, -80.0),
+                    radius: Bumper::RADIUS,
+                },
+                Obstacle::Portals {
+                    a: vec2(-520.0, -160.0),
+                    b: vec2(520.0, -160.0),
+                    radius: Portal::RADIUS,
+                },
+            ],
+            GameMode::TimeAttack => vec![
+                Obstacle::Bumper {
+                    translation: vec2(0.0, -80.0),
+                    radius: Bumper::RADIUS,
+                },
+                Obstacle::Wall {
+                    translation: vec2(-300.0, -120.0),
+                    size: vec2(120.0, 15.0),
+                },
+                Obstacle::Wall {
+                    translation: vec2(300.0, -120.0),
+                    size: vec2(120.0, 15.0),
+                },
+            ],
+            GameMode::Puzzle => vec![
+                Obstacle::Wall {
+                    translation: vec2(-200.0, -60.0),
+                    size: vec2(160.0, 15.0),
+                },
+                Obstacle::Wall {
+                    translation: vec2(200.0, -60.0),
+                    size: vec2(160.0, 15.0),
+                },
+            ],
+        }
+    }
+}
+
+/// Outcome of a cleared field, see [GameMode::field_cleared]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldCleared {
+    /// Spawn the level at this index in [GameMode::levels], or a random field
+    NextLevel(usize),
+    Won,
+}
+
+/// Returns `true` if a block with its bottom edge at `block_bottom` went down
+/// to the top of the paddles
+pub fn reached_paddle(block_bottom: f32) -> bool {
+    block_bottom <= Paddle::START.y + 0.5 * Paddle::SIZE.y
+}
+
+/// Rows of a fixed level, one character per column: `.` is empty, `b`, `h` and
+/// `g` are [BlockKind::Basic], [BlockKind::Hard] and [BlockKind::Gold]
+pub type Level = [&'static str; 5];
+
+const CLASSIC_LEVELS: [Level; 3] = [
+    [
+        "bbbbbbbbbbbb",
+        "bbbbbbbbbbbb",
+        "............",
+        "hbbbbbbbbbbh",
+        "............",
+    ],
+    [
+        "g..........g",
+        ".hhhhhhhhhh.",
+        ".bbbbbbbbbb.",
+        "..bbbbbbbb..",
+        "...bbbbbb...",
+    ],
+    [
+        "hhhhhhhhhhhh",
+        "hggbbbbbbggh",
+        "hbbbbbbbbbbh",
+        "h..........h",
+        "hbbbbbbbbbbh",
+    ],
+];
+
+const PUZZLE_LEVELS: [Level; 1] = [[
+    "....gggg....",
+    "...hhhhhh...",
+    "..h......h..",
+    "..h.bbbb.h..",
+    "..hhhhhhhh..",
+]];
+
+/// Progress of the current run through its [GameMode]
+#[derive(Resource, Debug, Clone)]
+pub struct ModeProgress {
+    /// Index in [GameMode::levels]
+    pub level: usize,
+    pub time_left: Option<Timer>,
+}
+
+#[derive(Component)]
+struct ModeText;
+
+/// Spawn the blocks of `level`, or a random field if the mode has no fixed levels
+pub fn spawn_level(
+    commands: &mut Commands,
+    main_box: MainBox,
+    mode: GameMode,
+    level: usize,
+    rng: &mut impl Rng,
+) {
+    if mode.is_versus() {
+        spawn_versus_walls(commands, main_box, rng);
+        return;
+    }
+
+    let Some(rows) = mode.levels().get(level) else {
+        spawn_blocks(commands, main_box, rng);
+        return;
+    };
+
+    for (row, line) in rows.iter().enumerate() {
+        for (col, cell) in line.chars().enumerate() {
+            let kind = match cell {
+                'b' => BlockKind::Basic,
+                'h' => BlockKind::Hard,
+                'g' => BlockKind::Gold,
+                _ => continue,
+            };
+
+            let translation = block_translation(main_box, col as i32, row as i32);
+            commands.spawn(BlockBundle::from_kind_translation(kind, translation));
+        }
+    }
+}
+
+fn setup_mode(mut commands: Commands, mode: Res<GameMode>) {
+    commands.insert_resource(ModeProgress {
+        level: 0,
+        time_left: mode
+            .time_limit()
+            .map(|limit| Timer::new(limit, TimerMode::Once)),
+    });
+
+    commands.spawn((
+        ModeText,
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: Scoreboard::FONT_SIZE,
+                    color: Scoreboard::TEXT_COLOR,
+                    ..default()
+                },
+            ),
+            // Below the combo meter
+            transform: Transform::from_translation(
+                camera::WINDOW_TOP_RIGHT.extend(0.0)
+                    - Vec3::Y * (Combo::FONT_SIZE + 2.0 * Combo::BAR_SIZE.y),
+            ),
+            text_anchor: Anchor::TopRight,
+            ..default()
+        },
+    ));
+}
+
+fn tick_time_limit(
+    time: Res<Time>,
+    mut progress: ResMut<ModeProgress>,
+    mut state: ResMut<NextState<AppState>>,
+) {
+    let Some(timer) = &mut progress.time_left else {
+        return;
+    };
+
+    if timer.tick(time.delta()).just_finished() {
+        state.set(AppState::GameWon);
+    }
+}
+
+/// Award the clear bonus and move on once every block is destroyed
+#[allow(clippy::too_many_arguments)]
+fn check_field_cleared(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    main_box: Res<MainBox>,
+    lives: Res<Lives>,
+    mut progress: ResMut<ModeProgress>,
+    mut score: ResMut<Scoreboard>,
+    mut rng: ResMut<GameRng>,
+    mut state: ResMut<NextState<AppState>>,
+    query: Query<(), With<Block>>,
+) {
+    if !query.is_empty() {
+        return;
+    }
+    let Some(cleared) = mode.field_cleared(progress.level) else {
+        return;
+    };
+
+    **score += mode.clear_score(**lives);
+
+    match cleared {
+        FieldCleared::NextLevel(level) => {
+            progress.level = level;
+            spawn_level(&mut commands, *main_box, *mode, level, &mut rng.field);
+        }
+        FieldCleared::Won => state.set(AppState::GameWon),
+    }
+}
+
+/// The run is lost once the descending blocks reach the paddles
+fn check_blocks_reached_paddle(
+    mode: Res<GameMode>,
+    mut state: ResMut<NextState<AppState>>,
+    query: Query<&Transform, With<Block>>,
+) {
+    if !mode.descends() {
+        return;
+    }
+
+    let reached = query
+        .iter()
+        .any(|transform| reached_paddle(transform.translation.y - 0.5 * Block::SIZE.y));
+    if reached {
+        state.set(AppState::GameOver);
+    }
+}
+
+fn update_mode_text(
+    mode: Res<GameMode>,
+    progress: Res<ModeProgress>,
+    mut query: Query<&mut Text, With<ModeText>>,
+) {
+    let Ok(mut text) = query.get_single_mut() else {
+        return;
+    };
+
+    let mut value = mode.name().to_string();
+    if let Some(timer) = &progress.time_left {
+        value += &format!("  {:.0}s", timer.remaining_secs().ceil());
+    } else if mode.levels().len() > 1 {
+        value += &format!("  Level {}/{}", progress.level + 1, mode.levels().len());
+    }
+
+    text.sections[0].value = value;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{WALL_HEIGHT, WALL_THICKNESS, WALL_WIDTH};
+    use super::*;
+
+    const ALL: [GameMode; 5] = [
+        GameMode::Classic,
+        GameMode::Endless,
+        GameMode::TimeAttack,
+        GameMode::Puzzle,
+        GameMode::Versus,
+    ];
+
+    #[test]
+    fn only_puzzle_overrides_the_lives() {
+        for mode in ALL {
+            let expected = (mode == GameMode::Puzzle).then_some(2);
+            assert_eq!(mode.lives(), expected, "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn only_endless_and_time_attack_descend() {
+        for mode in ALL {
+            let expected = matches!(mode, GameMode::Endless | GameMode::TimeAttack);
+            assert_eq!(mode.descends(), expected, "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn versus_has_no_powerups() {
+        for mode in ALL {
+            assert_eq!(mode.powerups(), !mode.is_versus(), "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn arena_obstacles_fit_between_the_paddle_and_the_walls() {
+        let half = 0.5 * vec2(WALL_WIDTH - WALL_THICKNESS, WALL_HEIGHT - WALL_THICKNESS);
+
+        assert!(GameMode::Classic.arena().is_empty());
+        assert!(GameMode::Versus.arena().is_empty());
+        for mode in [GameMode::Endless, GameMode::TimeAttack, GameMode::Puzzle] {
+            let arena = mode.arena();
+            assert!(!arena.is_empty(), "{mode:?}");
+
+            for obstacle in arena {
+                let (centers, extent) = match obstacle {
+                    Obstacle::Wall { translation, size } => (vec![translation], 0.5 * size),
+                    Obstacle::Bumper {
+                        translation,
+                        radius,
+                    } => (vec![translation], Vec2::splat(radius)),
+                    Obstacle::Portals { a, b, radius } => (vec![a, b], Vec2::splat(radius)),
+                };
+                for center in centers {
+                    assert!(
+                        (center.abs() + extent).cmplt(half).all(),
+                        "{mode:?} {obstacle:?}"
+                    );
+                    assert!(
+                        center.y - extent.y > Paddle::START.y,
+                        "{mode:?} {obstacle:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn time_attack_refills_the_field_until_the_time_runs_out() {
+        assert!(GameMode::TimeAttack.time_limit().is_some());
+        for level in 0..3 {
+            assert_eq!(
+                GameMode::TimeAttack.field_cleared(level),
+                Some(FieldCleared::NextLevel(0))
+            );
+        }
+        for mode in ALL.into_iter().filter(|&m| m != GameMode::TimeAttack) {
+            assert_eq!(mode.time_limit(), None, "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn puzzle_is_won_on_the_first_clear_with_a_bonus_per_ball_left() {
+        assert_eq!(GameMode::Puzzle.field_cleared(0), Some(FieldCleared::Won));
+        assert_eq!(GameMode::Puzzle.clear_score(0), 1000);
+        assert_eq!(GameMode::Puzzle.clear_score(2), 3000);
+    }
+
+    #[test]
+    fn classic_goes_through_every_level() {
+        let last = GameMode::Classic.levels().len() - 1;
+        assert_eq!(
+            GameMode::Classic.field_cleared(0),
+            Some(FieldCleared::NextLevel(1))
+        );
+        assert_eq!(
+            GameMode::Classic.field_cleared(last),
+            Some(FieldCleared::Won)
+        );
+        assert_eq!(GameMode::Endless.field_cleared(0), None);
+        assert_eq!(GameMode::Versus.field_cleared(0), None);
+    }
+
+    #[test]
+    fn blocks_reach_the_paddle_at_its_top_edge() {
+        let top = Paddle::START.y + 0.5 * Paddle::SIZE.y;
+        assert!(reached_paddle(top));
+        assert!(reached_paddle(top - 1.0));
+        assert!(!reached_paddle(top + 1.0));
+    }
+}
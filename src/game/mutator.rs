@@ -0,0 +1,257 @@
+use std::collections::BTreeSet;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::AppState;
+
+use super::{
+    ball::{Ball, BallLost},
+    block::BlockDamaged,
+    boss::PaddleHitByProjectile,
+    damage_blocks,
+    skill::{Modifiers, Rule, Stat, StatModifier},
+};
+
+/// Every active [Mutator] is made of a [Stat] change, a [Rule] or both, combined
+/// with the skills into the [Modifiers] that the systems read. Only the reactions
+/// to the rules that no other system owns are handled here.
+pub struct MutatorPlugin;
+
+impl Plugin for MutatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Mutators>().add_systems(
+            FixedUpdate,
+            (
+                shatter_balls.after(damage_blocks),
+                end_run_on_projectile_hit,
+            )
+                .run_if(in_state(AppState::Game)),
+        );
+    }
+}
+
+/// Optional rule changing a whole run, picked before it starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Mutator {
+    DoubleSpeed,
+    NoPowerups,
+    OneHitPaddle,
+    RandomHealth,
+    Mirrored,
+    Glass,
+}
+
+impl Mutator {
+    pub const ALL: [Mutator; 6] = [
+        Mutator::DoubleSpeed,
+        Mutator::NoPowerups,
+        Mutator::OneHitPaddle,
+        Mutator::RandomHealth,
+        Mutator::Mirrored,
+        Mutator::Glass,
+    ];
+
+    /// Block hits a [Mutator::Glass] ball survives
+    pub const GLASS_HITS: u32 = 5;
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mutator::DoubleSpeed => "Double speed",
+            Mutator::NoPowerups => "No powerups",
+            Mutator::OneHitPaddle => "One hit paddle",
+            Mutator::RandomHealth => "Random block health",
+            Mutator::Mirrored => "Mirrored controls",
+            Mutator::Glass => "Glass balls",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Mutator::DoubleSpeed => "balls move twice as fast",
+            Mutator::NoPowerups => "blocks never drop powerups",
+            Mutator::OneHitPaddle => "no extra balls, a projectile hit ends the run",
+            Mutator::RandomHealth => "blocks spawn with up to twice their health",
+            Mutator::Mirrored => "left and right are swapped",
+            Mutator::Glass => "balls hit twice as hard but shatter after a few hits",
+        }
+    }
+
+    /// Factor applied to the points of every destroyed block
+    pub fn score_multiplier(self) -> f32 {
+        match self {
+            Mutator::DoubleSpeed => 1.5,
+            Mutator::NoPowerups => 1.3,
+            Mutator::OneHitPaddle => 2.0,
+            Mutator::RandomHealth => 1.1,
+            Mutator::Mirrored => 1.25,
+            Mutator::Glass => 1.2,
+        }
+    }
+
+    pub fn modifier(self) -> Option<(Stat, StatModifier)> {
+        match self {
+            Mutator::DoubleSpeed => Some((Stat::BallSpeed, StatModifier::percent(1.0))),
+            // The base chance is 1, this brings it to 0 whatever the percent bonuses
+            Mutator::NoPowerups => Some((Stat::PowerupChance, StatModifier::flat(-1.0))),
+            Mutator::OneHitPaddle => Some((Stat::Lives, StatModifier::percent(-1.0))),
+            Mutator::RandomHealth => Some((Stat::BlockHealthVariance, StatModifier::flat(1.0))),
+            Mutator::Glass => Some((Stat::Attack, StatModifier::percent(1.0))),
+            Mutator::Mirrored => None,
+        }
+    }
+
+    pub fn rule(self) -> Option<Rule> {
+        match self {
+            Mutator::OneHitPaddle => Some(Rule::FatalProjectiles),
+            Mutator::Mirrored => Some(Rule::MirroredControls),
+            Mutator::Glass => Some(Rule::ShatteringBalls),
+            Mutator::DoubleSpeed | Mutator::NoPowerups | Mutator::RandomHealth => None,
+        }
+    }
+}
+
+/// [Mutator]s enabled for the next runs
+#[derive(Resource, Debug, Clone, Default, PartialEq, Deref)]
+pub struct Mutators(BTreeSet<Mutator>);
+
+impl Mutators {
+    pub fn toggle(&mut self, mutator: Mutator) {
+        if !self.0.remove(&mutator) {
+            self.0.insert(mutator);
+        }
+    }
+
+    pub fn score_multiplier(&self) -> f32 {
+        self.iter().map(|m| m.score_multiplier()).product()
+    }
+
+    pub fn modifiers(&self) -> impl Iterator<Item = (Stat, StatModifier)> + '_ {
+        self.iter().filter_map(|m| m.modifier())
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = Rule> + '_ {
+        self.iter().filter_map(|m| m.rule())
+    }
+
+    /// Lines shown on the end screen, empty if no mutator is active
+    pub fn summary(&self) -> Vec<String> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+
+        let names: Vec<_> = self.iter().map(|m| m.name()).collect();
+        vec![
+            format!("Mutators: {}", names.join(", ")),
+            format!("Mutator score multiplier: x{:.2}", self.score_multiplier()),
+        ]
+    }
+}
+
+/// Block hits taken by a ball under [Rule::ShatteringBalls], it shatters at
+/// [Mutator::GLASS_HITS]
+#[derive(Component, Clone, Copy, Default, Deref, DerefMut)]
+struct GlassHits(u32);
+
+fn shatter_balls(
+    mut commands: Commands,
+    modifiers: Res<Modifiers>,
+    mut block_damaged: EventReader<BlockDamaged>,
+    mut ball_lost: EventWriter<BallLost>,
+    query: Query<Option<&GlassHits>, With<Ball>>,
+) {
+    if !modifiers.has(Rule::ShatteringBalls) {
+        block_damaged.clear();
+        return;
+    }
+
+    let mut new_hits = HashMap::<Entity, u32>::new();
+    for ball in block_damaged.read().filter_map(|d| d.ball) {
+        *new_hits.entry(ball).or_default() += 1;
+    }
+
+    for (ball, count) in new_hits {
+        let Ok(hits) = query.get(ball) else {
+            continue;
+        };
+
+        let hits = hits.map_or(0, |h| **h) + count;
+        if hits >= Mutator::GLASS_HITS {
+            commands.entity(ball).despawn();
+            ball_lost.send(BallLost { defender: None });
+        } else {
+            commands.entity(ball).insert(GlassHits(hits));
+        }
+    }
+}
+
+fn end_run_on_projectile_hit(
+    modifiers: Res<Modifiers>,
+    mut hits: EventReader<PaddleHitByProjectile>,
+    mut state: ResMut<NextState<AppState>>,
+) {
+    if hits.read().count() > 0 && modifiers.has(Rule::FatalProjectiles) {
+        state.set(AppState::GameOver);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_mutator_keeps_the_score() {
+        assert_eq!(Mutators::default().score_multiplier(), 1.0);
+    }
+
+    #[test]
+    fn active_multipliers_stack() {
+        let mut mutators = Mutators::default();
+        mutators.toggle(Mutator::DoubleSpeed);
+        mutators.toggle(Mutator::OneHitPaddle);
+
+        assert!((mutators.score_multiplier() - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn toggling_twice_removes_the_mutator() {
+        let mut mutators = Mutators::default();
+        mutators.toggle(Mutator::Glass);
+        mutators.toggle(Mutator::Glass);
+
+        assert_eq!(mutators.score_multiplier(), 1.0);
+        assert!(mutators.summary().is_empty());
+    }
+
+    #[test]
+    fn rules_and_stats_come_from_the_active_mutators() {
+        let mut mutators = Mutators::default();
+        mutators.toggle(Mutator::OneHitPaddle);
+        mutators.toggle(Mutator::Mirrored);
+
+        let rules: Vec<_> = mutators.rules().collect();
+        assert_eq!(rules, [Rule::FatalProjectiles, Rule::MirroredControls]);
+
+        let mut modifiers = Modifiers::default();
+        for (stat, modifier) in mutators.modifiers() {
+            modifiers.add(stat, modifier);
+        }
+        assert_eq!(modifiers.apply(Stat::Lives, 3.0), 0.0);
+    }
+
+    #[test]
+    fn every_mutator_changes_the_game() {
+        for mutator in Mutator::ALL {
+            assert!(
+                mutator.modifier().is_some() || mutator.rule().is_some(),
+                "{mutator:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn every_mutator_rewards_the_player() {
+        for mutator in Mutator::ALL {
+            assert!(mutator.score_multiplier() > 1.0, "{mutator:?}");
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use bevy::{
+    math::bounding::BoundingCircle,
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use crate::AppState;
+
+use super::{
+    apply_velocity,
+    ball::{Ball, BallCollision, SpeedBoost},
+    check_ball_collision, cleanup_component, collide_volume, GameMode, Velocity, WallBundle,
+};
+
+pub struct ObstaclePlugin;
+
+impl Plugin for ObstaclePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Game), setup_obstacles)
+            .add_systems(
+                FixedUpdate,
+                (check_bumper_collision, check_portals)
+                    .after(apply_velocity)
+                    .before(check_ball_collision)
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                OnExit(AppState::Game),
+                (cleanup_component::<Bumper>, cleanup_component::<Portal>),
+            );
+    }
+}
+
+/// Something placed inside the arena, in addition to the outer walls
+#[derive(Clone, Copy, Debug)]
+pub enum Obstacle {
+    Wall {
+        translation: Vec2,
+        size: Vec2,
+    },
+    Bumper {
+        translation: Vec2,
+        radius: f32,
+    },
+    /// A ball entering one portal comes out of the other with the same velocity
+    Portals {
+        a: Vec2,
+        b: Vec2,
+        radius: f32,
+    },
+}
+
+#[derive(Component, Clone)]
+pub struct Bumper {
+    pub radius: f32,
+}
+
+impl Bumper {
+    pub const RADIUS: f32 = 25.0;
+    pub const COLOR: Color = Color::CRIMSON;
+    /// [SpeedBoost] given to a ball bouncing on a bumper
+    pub const BOOST: f32 = 200.0;
+}
+
+#[derive(Component, Clone)]
+pub struct Portal {
+    pub radius: f32,
+    pub exit: Entity,
+}
+
+impl Portal {
+    pub const RADIUS: f32 = 20.0;
+    pub const COLOR: Color = Color::PURPLE;
+}
+
+fn round_mesh(
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    translation: Vec2,
+    radius: f32,
+    color: Color,
+) -> MaterialMesh2dBundle<ColorMaterial> {
+    MaterialMesh2dBundle {
+        mesh: Mesh2dHandle(meshes.add(Circle::new(radius))),
+        material: materials.add(color),
+        transform: Transform::from_translation(translation.extend(0.0)),
+        ..default()
+    }
+}
+
+fn setup_obstacles(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mode: Res<GameMode>,
+) {
+    for obstacle in mode.arena() {
+        match obstacle {
+            Obstacle::Wall { translation, size } => {
+                commands.spawn(WallBundle::new(translation, size));
+            }
+            Obstacle::Bumper {
+                translation,
+                radius,
+            } => {
+                commands.spawn((
+                    Bumper { radius },
+                    round_mesh(
+                        &mut meshes,
+                        &mut materials,
+                        translation,
+                        radius,
+                        Bumper::COLOR,
+                    ),
+                ));
+            }
+            Obstacle::Portals { a, b, radius } => {
+                let portal_a = commands.spawn_empty().id();
+                let portal_b = commands.spawn_empty().id();
+
+                commands.entity(portal_a).insert((
+                    Portal {
+                        radius,
+                        exit: portal_b,
+                    },
+                    round_mesh(&mut meshes, &mut materials, a, radius, Portal::COLOR),
+                ));
+                commands.entity(portal_b).insert((
+                    Portal {
+                        radius,
+                        exit: portal_a,
+                    },
+                    round_mesh(&mut meshes, &mut materials, b, radius, Portal::COLOR),
+                ));
+            }
+        }
+    }
+}
+
+/// Reflect the balls along the bumper surface normal and speed them up
+fn check_bumper_collision(
+    mut commands: Commands,
+    collision_sound: Res<BallCollision>,
+    bumpers: Query<(&Transform, &Bumper), Without<Ball>>,
+    mut balls: Query<(&mut Transform, &mut Velocity, &mut SpeedBoost, &Ball)>,
+) {
+    for (bumper_t, bumper) in &bumpers {
+        let center = bumper_t.translation.truncate();
+
+        for (mut ball_t, mut ball_v, mut boost, ball) in &mut balls {
+            let ball_center = ball_t.translation.truncate();
+            let collision = collide_volume(
+                BoundingCircle::new(ball_center, ball.radius()),
+                BoundingCircle::new(center, bumper.radius),
+            );
+            if collision.is_none() {
+                continue;
+            }
+
+            let normal = (ball_center - center).try_normalize().unwrap_or(Vec2::Y);
+
+            // Move the ball on the bumper surface
+            let surface = center + normal * (bumper.radius + ball.radius());
+            ball_t.translation = surface.extend(ball_t.translation.z);
+
+            let towards = ball_v.dot(normal);
+            if towards < 0.0 {
+                ball_v.0 -= 2.0 * towards * normal;
+            }
+            **boost = Bumper::BOOST;
+
+            commands.spawn(AudioBundle {
+                source: collision_sound.clone(),
+                settings: PlaybackSettings::DESPAWN,
+            });
+        }
+    }
+}
+
+/// Move the balls entering a portal out of the paired one
+fn check_portals(
+    portals: Query<(&Transform, &Portal), Without<Ball>>,
+    mut balls: Query<(&mut Transform, &Velocity, &Ball)>,
+) {
+    for (portal_t, portal) in &portals {
+        let Ok((exit_t, exit)) = portals.get(portal.exit) else {
+            continue;
+        };
+
+        for (mut ball_t, ball_v, ball) in &mut balls {
+            let offset = ball_t.translation.truncate() - portal_t.translation.truncate();
+            if offset.length() > portal.radius {
+                continue;
+            }
+
+            // Come out in front of the exit, so the ball doesn't enter it right away
+            let out = ball_v.normalize_or_zero() * (exit.radius + ball.radius() + 1.0);
+            ball_t.translation = (exit_t.translation.truncate() + out).extend(ball_t.translation.z);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use chrono::NaiveDate;
+
+    use crate::daily::DailyChallenge;
+
+    use super::super::Wall;
+    use super::*;
+
+    #[test]
+    fn daily_challenge_spawns_the_arena_of_its_mode() {
+        for day in 1..=28 {
+            let date = NaiveDate::from_ymd_opt(2024, 2, day).unwrap();
+            let challenge = DailyChallenge::for_date(date);
+
+            let mut world = World::new();
+            world.init_resource::<Assets<Mesh>>();
+            world.init_resource::<Assets<ColorMaterial>>();
+            world.insert_resource(challenge.mode);
+            world.run_system_once(setup_obstacles);
+
+            let arena = challenge.mode.arena();
+            let count = |f: fn(&Obstacle) -> usize| arena.iter().map(f).sum::<usize>();
+            let walls = count(|o| matches!(o, Obstacle::Wall { .. }) as usize);
+            let bumpers = count(|o| matches!(o, Obstacle::Bumper { .. }) as usize);
+            let portals = count(|o| 2 * matches!(o, Obstacle::Portals { .. }) as usize);
+
+            let mode = challenge.mode;
+            assert_eq!(
+                world.query::<&Wall>().iter(&world).count(),
+                walls,
+                "{mode:?}"
+            );
+            assert_eq!(
+                world.query::<&Bumper>().iter(&world).count(),
+                bumpers,
+                "{mode:?}"
+            );
+            assert_eq!(
+                world.query::<&Portal>().iter(&world).count(),
+                portals,
+                "{mode:?}"
+            );
+        }
+    }
+}
@@ -1,53 +1,290 @@
 use bevy::{math::*, prelude::*};
 
-use super::{level::Level, *};
+use super::{
+    level::Level,
+    skill::{Modifiers, Rule, Stat},
+    *,
+};
 
 pub struct PaddlePlugin;
 
 impl Plugin for PaddlePlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<LevelUp>().add_event::<ExpUp>().add_systems(
-            Update,
-            (move_paddle, handle_paddle_timer, level_up).run_if(in_state(AppState::Game)),
-        );
+        app.add_event::<LevelUp>()
+            .add_event::<ExpUp>()
+            .add_event::<PaddleHit>()
+            .init_resource::<PaddleDeflection>()
+            .init_resource::<PaddleMovement>()
+            .init_resource::<Coop>()
+            .add_systems(
+                Update,
+                (
+                    move_paddle,
+                    handle_paddle_timer,
+                    handle_paddle_stun,
+                    level_up,
+                )
+                    .run_if(in_state(AppState::Game)),
+            );
     }
 }
 
 #[derive(Component, Clone)]
-pub struct Paddle;
+pub struct Paddle {
+    /// Size of the paddle when no powerup or attack resized it
+    pub base_size: Vec2,
+}
+
+/// Who controls a paddle, also credited for what the balls it last touched do
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Player {
+    #[default]
+    One,
+    Two,
+}
+
+impl Player {
+    pub const ALL: [Player; 2] = [Player::One, Player::Two];
+
+    pub fn index(self) -> usize {
+        match self {
+            Player::One => 0,
+            Player::Two => 1,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Player::One => "P1",
+            Player::Two => "P2",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Player::One => Paddle::COLOR,
+            Player::Two => Color::rgb(0.7, 0.3, 0.3),
+        }
+    }
+
+    pub fn opponent(self) -> Self {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+
+    /// Sign of the y of the edge defended in versus games, P1 defends the bottom
+    pub fn versus_edge(self) -> f32 {
+        match self {
+            Player::One => -1.0,
+            Player::Two => 1.0,
+        }
+    }
+}
+
+/// Local two-player game, each [Player] with their own paddle
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct Coop {
+    pub enabled: bool,
+}
+
+impl Coop {
+    /// [Player]s in the next runs
+    pub fn players(self) -> &'static [Player] {
+        if self.enabled {
+            &Player::ALL
+        } else {
+            &Player::ALL[..1]
+        }
+    }
+}
+
+/// Keys moving a paddle
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PaddleControls {
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub dash: KeyCode,
+}
+
+impl PaddleControls {
+    pub fn for_player(player: Player) -> Self {
+        match player {
+            Player::One => Self {
+                left: KeyCode::ArrowLeft,
+                right: KeyCode::ArrowRight,
+                dash: KeyCode::Space,
+            },
+            Player::Two => Self {
+                left: KeyCode::KeyA,
+                right: KeyCode::KeyD,
+                dash: KeyCode::ShiftLeft,
+            },
+        }
+    }
+}
+
+/// Horizontal speed of the paddle
+#[derive(Component, Clone, Copy, Default, Deref, DerefMut)]
+pub struct PaddleVelocity(pub f32);
+
+/// How the paddle bounces the balls back: the hit offset from the paddle center
+/// picks the outgoing angle and the paddle motion adds some spin to it.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct PaddleDeflection {
+    /// Angle from the vertical of a ball hitting the paddle edge, in radians
+    pub max_angle: f32,
+    /// Fraction of the paddle velocity transferred to the ball
+    pub spin: f32,
+    /// Minimum vertical component of the outgoing direction
+    pub min_vertical: f32,
+}
+
+impl Default for PaddleDeflection {
+    fn default() -> Self {
+        Self {
+            max_angle: std::f32::consts::FRAC_PI_3,
+            spin: 0.3,
+            min_vertical: 0.3,
+        }
+    }
+}
+
+impl PaddleDeflection {
+    /// Outgoing velocity of a ball moving at `speed` that hit a paddle of width
+    /// `width` at `offset` from its center, while the paddle moved at `paddle_velocity`.
+    pub fn bounce(&self, offset: f32, width: f32, paddle_velocity: f32, speed: f32) -> Vec2 {
+        let hit = (offset / (0.5 * width)).clamp(-1.0, 1.0);
+        let angle = hit * self.max_angle;
+
+        let mut dir = vec2(angle.sin(), angle.cos());
+        dir.x += self.spin * paddle_velocity / speed;
+        dir = dir.normalize();
+
+        let min_vertical = self.min_vertical.clamp(0.0, 1.0);
+        if dir.y < min_vertical {
+            dir = vec2(
+                dir.x.signum() * (1.0 - min_vertical.powi(2)).sqrt(),
+                min_vertical,
+            );
+        }
+
+        dir * speed
+    }
+}
+
+/// Balance of the paddle movement
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct PaddleMovement {
+    /// Accelerate and slow down with friction instead of moving at constant speed
+    pub inertia: bool,
+    pub top_speed: f32,
+    pub acceleration: f32,
+    pub friction: f32,
+    pub dash_speed: f32,
+    /// Seconds
+    pub dash_duration: f32,
+    /// Seconds
+    pub dash_cooldown: f32,
+}
+
+impl Default for PaddleMovement {
+    fn default() -> Self {
+        let tuning = Tuning::default();
+        Self {
+            inertia: tuning.paddle_inertia,
+            top_speed: tuning.paddle_speed,
+            acceleration: tuning.paddle_acceleration,
+            friction: tuning.paddle_friction,
+            dash_speed: tuning.dash_speed,
+            dash_duration: tuning.dash_duration,
+            dash_cooldown: tuning.dash_cooldown,
+        }
+    }
+}
 
-#[derive(Event)]
-pub struct LevelUp(pub u32);
+impl PaddleMovement {
+    /// New velocity after `dt` seconds of input towards `direction`, or of friction
+    /// if there is no input.
+    pub fn accelerate(&self, velocity: f32, direction: f32, dt: f32) -> f32 {
+        let velocity = if direction != 0.0 {
+            velocity + direction * self.acceleration * dt
+        } else {
+            velocity - velocity.signum() * (self.friction * dt).min(velocity.abs())
+        };
 
-#[derive(Event, Deref, DerefMut)]
-pub struct ExpUp(pub u32);
+        velocity.clamp(-self.top_speed, self.top_speed)
+    }
+}
+
+/// The paddle can't move until the timer finishes
+#[derive(Component, Clone, Deref, DerefMut)]
+pub struct PaddleStun(pub Timer);
+
+/// State of the paddle dash, times are in seconds
+#[derive(Component, Clone, Copy, Default)]
+pub struct PaddleDash {
+    pub remaining: f32,
+    pub cooldown: f32,
+    pub direction: f32,
+}
+
+#[derive(Event, Clone, Copy)]
+pub struct LevelUp {
+    pub player: Player,
+    pub level: u32,
+}
+
+#[derive(Event, Clone, Copy)]
+pub struct ExpUp {
+    pub player: Player,
+    pub exp: u32,
+}
+
+/// A ball bounced on the paddle
+#[derive(Event, Clone, Copy)]
+pub struct PaddleHit;
 
 impl Paddle {
     pub const START: Vec2 = vec2(0., -250.);
     pub const SIZE: Vec2 = vec2(120., 20.);
-    pub const ENLARGED_SIZE: Vec2 = vec2(240.0, 20.);
+    pub const ENLARGE_FACTOR: f32 = 2.0;
+    pub const SHRINK_FACTOR: f32 = 0.5;
     pub const COLOR: Color = Color::rgb(0.3, 0.3, 0.7);
-    pub const SPEED: f32 = 500.0;
-    pub const TIMEOUT: f32 = 10.0;
+    /// Distance between the paddles of a [Coop] game
+    pub const COOP_SPACING: f32 = 300.0;
 
-    pub const LEVEL_UP_MULTIPLYER: f32 = 1.5;
-    pub const LEVEL_CAP: u32 = 50;
+    /// [Paddle::base_size] with the width scaled by `factor`
+    pub fn resized(&self, factor: f32) -> Vec2 {
+        vec2(self.base_size.x * factor, self.base_size.y)
+    }
 }
 
 #[derive(Bundle, Clone)]
 pub struct PaddleBundle {
     pub paddle: Paddle,
+    pub velocity: PaddleVelocity,
+    pub dash: PaddleDash,
     pub collider: Collider,
     pub level: Level,
+    pub player: Player,
+    pub controls: PaddleControls,
     pub sprite: SpriteBundle,
 }
 
 impl Default for PaddleBundle {
     fn default() -> Self {
         Self {
-            paddle: Paddle,
+            paddle: Paddle {
+                base_size: Paddle::SIZE,
+            },
+            velocity: PaddleVelocity::default(),
+            dash: PaddleDash::default(),
             collider: Collider { size: Paddle::SIZE },
             level: Level { level: 0, exp: 0 },
+            player: Player::One,
+            controls: PaddleControls::for_player(Player::One),
             sprite: SpriteBundle {
                 transform: Transform {
                     translation: Paddle::START.extend(0.0),
@@ -64,90 +301,231 @@ impl Default for PaddleBundle {
     }
 }
 
-#[derive(Resource)]
-pub struct PaddleEnlargedTimer {
+impl PaddleBundle {
+    pub fn with_size(size: Vec2) -> Self {
+        let mut bundle = Self::default();
+        bundle.paddle.base_size = size;
+        bundle.collider.size = size;
+        bundle.sprite.sprite.custom_size = Some(size);
+        bundle
+    }
+
+    /// Paddle of `player`, moved by `x` from the start
+    pub fn with_player(mut self, player: Player, x: f32) -> Self {
+        self.player = player;
+        self.controls = PaddleControls::for_player(player);
+        self.sprite.sprite.color = player.color();
+        self.sprite.transform.translation.x += x;
+        self
+    }
+
+    /// Paddle of `player` in front of the edge they defend in versus games
+    pub fn defending(self, player: Player) -> Self {
+        let mut bundle = self.with_player(player, 0.0);
+        bundle.sprite.transform.translation.y = player.versus_edge() * Paddle::START.y.abs();
+        bundle
+    }
+}
+
+/// The paddle goes back to its [Paddle::base_size] when the timer finishes
+#[derive(Component)]
+pub struct PaddleResizeTimer {
     pub timer: Timer,
 }
 
-impl Default for PaddleEnlargedTimer {
-    fn default() -> Self {
+impl PaddleResizeTimer {
+    pub fn from_secs(secs: f32) -> Self {
         Self {
-            timer: Timer::new(Duration::from_secs_f32(Paddle::TIMEOUT), TimerMode::Once),
+            timer: Timer::new(Duration::from_secs_f32(secs), TimerMode::Once),
         }
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn move_paddle(
-    mut query: Query<(&mut Transform, &Collider), With<Paddle>>,
+    mut query: Query<
+        (
+            &mut Transform,
+            &mut PaddleVelocity,
+            &mut PaddleDash,
+            &Collider,
+            &PaddleControls,
+            Has<PaddleStun>,
+        ),
+        With<Paddle>,
+    >,
     input: Res<ButtonInput<KeyCode>>,
+    movement: Res<PaddleMovement>,
+    modifiers: Res<Modifiers>,
     main_box: Res<MainBox>,
     time: Res<Time>,
 ) {
-    let (mut transform, collider) = query.single_mut();
-    let mut direction = 0.0;
+    let movement = PaddleMovement {
+        top_speed: modifiers.apply(Stat::PaddleSpeed, movement.top_speed),
+        dash_cooldown: modifiers.apply(Stat::DashCooldown, movement.dash_cooldown),
+        ..*movement
+    };
 
-    if input.pressed(KeyCode::ArrowLeft) {
-        direction += -1.0;
-    }
+    let dt = time.delta_seconds();
 
-    if input.pressed(KeyCode::ArrowRight) {
-        direction += 1.0;
-    }
+    for (mut transform, mut velocity, mut dash, collider, controls, stunned) in &mut query {
+        if stunned {
+            **velocity = 0.0;
+            continue;
+        }
+
+        let mut direction = 0.0;
+
+        if input.pressed(controls.left) {
+            direction += -1.0;
+        }
+
+        if input.pressed(controls.right) {
+            direction += 1.0;
+        }
+
+        if modifiers.has(Rule::MirroredControls) {
+            direction = -direction;
+        }
+
+        dash.remaining = (dash.remaining - dt).max(0.0);
+        dash.cooldown = (dash.cooldown - dt).max(0.0);
+
+        if input.just_pressed(controls.dash) && dash.cooldown == 0.0 && direction != 0.0 {
+            dash.remaining = movement.dash_duration;
+            dash.cooldown = movement.dash_cooldown;
+            dash.direction = direction;
+        }
 
-    direction = direction * Paddle::SPEED * time.delta_seconds();
+        **velocity = if dash.remaining > 0.0 {
+            dash.direction * movement.dash_speed
+        } else if movement.inertia {
+            movement.accelerate(**velocity, direction, dt)
+        } else {
+            direction * movement.top_speed
+        };
 
-    transform.translation.x += direction;
+        let max_x = (main_box.size.x - collider.size.x) * 0.5;
+        transform.translation.x += **velocity * dt;
 
-    transform.translation.x = transform.translation.x.clamp(
-        -(main_box.size.x - collider.size.x) * 0.5,
-        (main_box.size.x - collider.size.x) * 0.5,
-    );
+        // Stop against the walls
+        if transform.translation.x.abs() > max_x {
+            transform.translation.x = transform.translation.x.clamp(-max_x, max_x);
+            **velocity = 0.0;
+        }
+    }
 }
 
 fn handle_paddle_timer(
     mut commands: Commands,
-    timer: Option<ResMut<PaddleEnlargedTimer>>,
     time: Res<Time>,
-    mut query: Query<(&mut Sprite, &mut Collider), With<Paddle>>,
+    mut query: Query<(
+        Entity,
+        &mut PaddleResizeTimer,
+        &mut Sprite,
+        &mut Collider,
+        &Paddle,
+    )>,
 ) {
-    let Some(mut timer) = timer else { return };
+    for (entity, mut timer, mut sprite, mut collider, paddle) in &mut query {
+        timer.timer.tick(time.delta());
 
-    timer.timer.tick(time.delta());
+        if !timer.timer.finished() {
+            continue;
+        }
 
-    if !timer.timer.finished() {
-        return;
+        sprite.custom_size = Some(paddle.base_size);
+        collider.size = paddle.base_size;
+
+        commands.entity(entity).remove::<PaddleResizeTimer>();
     }
+}
 
-    let (mut sprite, mut collider) = query.single_mut();
-    sprite.custom_size = Some(Paddle::SIZE);
-    collider.size = Paddle::SIZE;
+fn handle_paddle_stun(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut PaddleStun)>,
+) {
+    for (entity, mut stun) in &mut query {
+        stun.tick(time.delta());
 
-    commands.remove_resource::<PaddleEnlargedTimer>();
+        if stun.finished() {
+            commands.entity(entity).remove::<PaddleStun>();
+        }
+    }
 }
 
 fn level_up(
+    tuning: Res<Tuning>,
     mut level_up: EventWriter<LevelUp>,
     mut exp_up: EventReader<ExpUp>,
-    mut query: Query<&mut Level, With<Paddle>>,
+    mut query: Query<(&mut Level, &Player), With<Paddle>>,
 ) {
     if exp_up.is_empty() {
         return;
     }
 
-    let mut level = query.single_mut();
+    let mut gained = [0; Player::ALL.len()];
     for exp in exp_up.read() {
-        level.exp += exp.0;
+        gained[exp.player.index()] += exp.exp;
     }
 
-    let level_cap = level_exp_cap(level.level);
+    for (mut level, &player) in &mut query {
+        if gained[player.index()] == 0 {
+            continue;
+        }
 
-    if level.exp > level_cap {
-        level.exp -= level_cap;
-        level.level += 1;
-        level_up.send(LevelUp(level.level));
+        for level in level.gain_exp(gained[player.index()], &tuning) {
+            level_up.send(LevelUp { player, level });
+        }
     }
 }
 
-pub fn level_exp_cap(level: u32) -> u32 {
-    (Paddle::LEVEL_CAP as f32 * (1. + level as f32 * Paddle::LEVEL_UP_MULTIPLYER)) as u32
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    #[test]
+    fn center_hit_goes_straight_up() {
+        let v = PaddleDeflection::default().bounce(0.0, 100.0, 0.0, 400.0);
+        assert!(v.x.abs() < EPSILON);
+        assert!((v.y - 400.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn edge_hit_uses_max_angle() {
+        let deflection = PaddleDeflection::default();
+        let v = deflection.bounce(50.0, 100.0, 0.0, 1.0);
+        assert!((v.x - deflection.max_angle.sin()).abs() < EPSILON);
+        assert!((v.y - deflection.max_angle.cos()).abs() < EPSILON);
+
+        let past_edge = deflection.bounce(80.0, 100.0, 0.0, 1.0);
+        assert!((past_edge - v).length() < EPSILON);
+    }
+
+    #[test]
+    fn hits_are_mirrored_around_the_center() {
+        let deflection = PaddleDeflection::default();
+        let left = deflection.bounce(-20.0, 100.0, 0.0, 300.0);
+        let right = deflection.bounce(20.0, 100.0, 0.0, 300.0);
+        assert!((left.x + right.x).abs() < EPSILON);
+        assert!((left.y - right.y).abs() < EPSILON);
+    }
+
+    #[test]
+    fn paddle_motion_adds_spin_and_keeps_speed() {
+        let v = PaddleDeflection::default().bounce(0.0, 100.0, 200.0, 400.0);
+        assert!(v.x > 0.0);
+        assert!((v.length() - 400.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn outgoing_direction_is_never_too_flat() {
+        let deflection = PaddleDeflection::default();
+        let v = deflection.bounce(50.0, 100.0, 10_000.0, 1.0);
+        assert!(v.y >= deflection.min_vertical - EPSILON);
+        assert!((v.length() - 1.0).abs() < EPSILON);
+    }
 }
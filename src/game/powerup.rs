@@ -1,19 +1,26 @@
 use bevy::prelude::*;
 use rand::prelude::*;
+use serde::Serialize;
 
 use super::*;
 
-const POWERUP_RNGS: [(PowerupClass, f32); 3] = [
+const POWERUP_RNGS: [(PowerupClass, f32); 6] = [
     (PowerupClass::DuplicateBall, 0.2),
     (PowerupClass::EnlargeBall, 0.2),
     (PowerupClass::EnlargePaddle, 0.2),
+    (PowerupClass::FireBall, 0.1),
+    (PowerupClass::IceBall, 0.1),
+    (PowerupClass::PoisonBall, 0.1),
 ];
 
-#[derive(Component, Clone, Copy, Debug)]
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub enum PowerupClass {
     DuplicateBall,
     EnlargeBall,
     EnlargePaddle,
+    FireBall,
+    IceBall,
+    PoisonBall,
 }
 
 #[derive(Component, Clone, Copy, Debug)]
@@ -22,6 +29,10 @@ pub struct Powerup {
     pub class: PowerupClass,
 }
 
+/// The paddle caught a [Powerup]
+#[derive(Event, Clone, Copy)]
+pub struct PowerupCollected(pub PowerupClass);
+
 #[derive(Bundle)]
 pub struct PowerupBundle {
     pub sprite: SpriteBundle,
@@ -32,17 +43,15 @@ pub struct PowerupBundle {
 
 impl Powerup {
     const SIZE: Vec2 = Vec2::new(15.0, 15.0);
-    const SPEED: Vec2 = Vec2::new(0.0, -50.0);
     /// This is equal to half a turn per sec
     const ROTATION_SPEED: f32 = std::f32::consts::PI;
 
-    /// Get a random [Powerup]
-    fn next_rng() -> Option<Self> {
-        let mut rng = rand::thread_rng();
+    /// Get a random [Powerup], `chance` scales the drop probability
+    fn next_rng(chance: f32, rng: &mut impl Rng) -> Option<Self> {
         let index = rng.gen_range(0..POWERUP_RNGS.len());
         let prob = rng.gen::<f32>();
 
-        if prob < POWERUP_RNGS[index].1 {
+        if prob < POWERUP_RNGS[index].1 * chance {
             return Some(Self {
                 size: Self::SIZE,
                 class: POWERUP_RNGS[index].0,
@@ -52,10 +61,14 @@ impl Powerup {
     }
 
     fn get_powerup_bundle(powerup: Powerup, translation: Vec3) -> PowerupBundle {
-        let (color, size) = match powerup.class {
-            PowerupClass::DuplicateBall => (Color::RED, Self::SIZE),
-            PowerupClass::EnlargeBall => (Color::VIOLET, Self::SIZE),
-            PowerupClass::EnlargePaddle => (Color::MIDNIGHT_BLUE, Self::SIZE),
+        let size = powerup.size;
+        let color = match powerup.class {
+            PowerupClass::DuplicateBall => Color::RED,
+            PowerupClass::EnlargeBall => Color::VIOLET,
+            PowerupClass::EnlargePaddle => Color::MIDNIGHT_BLUE,
+            PowerupClass::FireBall => DamageType::Fire.color(),
+            PowerupClass::IceBall => DamageType::Ice.color(),
+            PowerupClass::PoisonBall => DamageType::Poison.color(),
         };
 
         PowerupBundle {
@@ -70,12 +83,33 @@ impl Powerup {
             },
             powerup,
             collider: PlayerCollider(Collider { size }),
-            velocity: Velocity(Self::SPEED),
+            // Set from the Tuning by apply_powerup_speed
+            velocity: Velocity(Vec2::ZERO),
         }
     }
 
-    pub fn spawn_powerup(commands: &mut Commands, translation: Vec3) {
-        let Some(powerup) = Powerup::next_rng() else {
+    /// Spawn a powerup of a random class, regardless of the drop chance
+    pub fn spawn_guaranteed_powerup(
+        commands: &mut Commands,
+        translation: Vec3,
+        rng: &mut impl Rng,
+    ) {
+        let index = rng.gen_range(0..POWERUP_RNGS.len());
+        let powerup = Self {
+            size: Self::SIZE,
+            class: POWERUP_RNGS[index].0,
+        };
+
+        commands.spawn(Self::get_powerup_bundle(powerup, translation));
+    }
+
+    pub fn spawn_powerup(
+        commands: &mut Commands,
+        translation: Vec3,
+        chance: f32,
+        rng: &mut impl Rng,
+    ) {
+        let Some(powerup) = Powerup::next_rng(chance, rng) else {
             return;
         };
 
@@ -87,7 +121,11 @@ pub struct PowerupPlugin;
 
 impl Plugin for PowerupPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (update_powerups).run_if(in_state(AppState::Game)))
+        app.add_event::<PowerupCollected>()
+            .add_systems(
+                Update,
+                (update_powerups, apply_powerup_speed).run_if(in_state(AppState::Game)),
+            )
             .add_systems(
                 FixedUpdate,
                 check_powerups_out_of_bounds
@@ -104,6 +142,15 @@ fn update_powerups(time: Res<Time>, mut query: Query<&mut Transform, With<Poweru
     }
 }
 
+/// Falling speed of the new powerups, and of every powerup when the [Tuning] changes
+fn apply_powerup_speed(tuning: Res<Tuning>, mut query: Query<(&mut Velocity, Ref<Powerup>)>) {
+    for (mut velocity, powerup) in &mut query {
+        if powerup.is_added() || tuning.is_changed() {
+            **velocity = Vec2::new(0.0, -tuning.powerup_speed);
+        }
+    }
+}
+
 fn check_powerups_out_of_bounds(
     mut commands: Commands,
     main_box: Res<MainBox>,
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::AppState;
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunSeed>()
+            .add_systems(OnEnter(AppState::Game), setup_rng);
+    }
+}
+
+/// Seed of the next runs, a random one is drawn for every run if `None`
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Deref, DerefMut)]
+pub struct RunSeed(pub Option<u64>);
+
+/// Seed pinned for the next run only, to replay the run that just ended
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq, Deref)]
+pub struct ReplaySeed(pub u64);
+
+/// Random number generators of the current run, one stream per use so that
+/// drawing more in one of them doesn't shift the others. The same seed always
+/// gives the same sequences of draws.
+#[derive(Resource)]
+pub struct GameRng {
+    pub seed: u64,
+    /// Layout of the blocks: levels, new rows, garbage rows and block health
+    pub field: StdRng,
+    /// Crits, damage variance and boss attacks
+    pub combat: StdRng,
+    /// Powerup drops
+    pub drops: StdRng,
+}
+
+impl GameRng {
+    pub fn seeded(seed: u64) -> Self {
+        let mut streams = StdRng::seed_from_u64(seed);
+        Self {
+            seed,
+            field: StdRng::seed_from_u64(streams.gen()),
+            combat: StdRng::seed_from_u64(streams.gen()),
+            drops: StdRng::seed_from_u64(streams.gen()),
+        }
+    }
+}
+
+pub fn setup_rng(
+    mut commands: Commands,
+    run_seed: Res<RunSeed>,
+    replay_seed: Option<Res<ReplaySeed>>,
+) {
+    let seed = replay_seed
+        .map(|replay| **replay)
+        .or(**run_seed)
+        .unwrap_or_else(|| rand::thread_rng().gen());
+    commands.remove_resource::<ReplaySeed>();
+
+    let rng = GameRng::seeded(seed);
+    info!("Run seed: {}", rng.seed);
+
+    commands.insert_resource(rng);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draws(rng: &mut StdRng) -> Vec<u32> {
+        (0..16).map(|_| rng.gen()).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_draws() {
+        let (mut a, mut b) = (GameRng::seeded(42), GameRng::seeded(42));
+        assert_eq!(draws(&mut a.field), draws(&mut b.field));
+        assert_eq!(draws(&mut a.combat), draws(&mut b.combat));
+        assert_eq!(draws(&mut a.drops), draws(&mut b.drops));
+    }
+
+    #[test]
+    fn different_seeds_give_different_draws() {
+        assert_ne!(
+            draws(&mut GameRng::seeded(42).field),
+            draws(&mut GameRng::seeded(43).field)
+        );
+    }
+
+    #[test]
+    fn streams_are_independent() {
+        let mut rng = GameRng::seeded(42);
+        assert_ne!(draws(&mut rng.field), draws(&mut rng.combat));
+
+        // Extra combat draws don't change the field
+        let mut busy = GameRng::seeded(42);
+        draws(&mut busy.combat);
+        assert_eq!(
+            draws(&mut busy.field),
+            draws(&mut GameRng::seeded(42).field)
+        );
+    }
+
+    #[test]
+    fn seeded_keeps_its_seed() {
+        assert_eq!(GameRng::seeded(7).seed, 7);
+    }
+}
@@ -2,30 +2,131 @@ use bevy::{prelude::*, sprite::Anchor};
 
 use crate::{camera, AppState};
 
+use super::{
+    paddle::{Coop, Player},
+    versus::VersusProgress,
+    GameMode, Lives,
+};
+
 pub struct ScoreboardPlugin;
 
 impl Plugin for ScoreboardPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(AppState::Game), setup_scoreboard)
             .add_systems(OnExit(AppState::Game), cleanup_scoreboard)
-            .add_systems(Update, update_scoreboard.run_if(in_state(AppState::Game)));
+            .add_systems(
+                Update,
+                (
+                    update_scoreboard,
+                    update_player_scores,
+                    update_versus_scoreboards,
+                )
+                    .run_if(in_state(AppState::Game)),
+            );
     }
 }
 
 #[derive(Resource, Clone, Copy, Deref, DerefMut)]
 pub struct Scoreboard(pub u32);
 
+/// Points credited to each [Player] for the blocks their balls destroyed
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct PlayerScores(pub [u32; Player::ALL.len()]);
+
+impl PlayerScores {
+    pub fn add(&mut self, player: Player, points: u32) {
+        self.0[player.index()] += points;
+    }
+
+    pub fn get(&self, player: Player) -> u32 {
+        self.0[player.index()]
+    }
+
+    /// "P1: N  P2: M"
+    pub fn summary(&self) -> String {
+        Player::ALL
+            .map(|player| format!("{}: {}", player.name(), self.get(player)))
+            .join("  ")
+    }
+}
+
+/// Score of the last finished run, kept after the [Scoreboard] is removed.
+#[derive(Resource, Clone, Copy)]
+pub struct FinalScore {
+    pub mode: GameMode,
+    pub score: u32,
+    /// Only set in [Coop] and versus games
+    pub players: Option<PlayerScores>,
+    /// Only set in versus games
+    pub winner: Option<Player>,
+}
+
 #[derive(Component, Clone)]
 pub struct ScoreboardText;
 
+/// [PlayerScores] shown under the [Scoreboard] in [Coop] games
+#[derive(Component, Clone)]
+pub struct PlayerScoresText;
+
+/// Scoreboard of a single [Player] in versus games, replacing the shared one
+#[derive(Component, Clone, Copy, Deref)]
+pub struct VersusScoreboardText(pub Player);
+
 impl Scoreboard {
     pub const FONT_SIZE: f32 = 40.0;
     pub const TEXT_COLOR: Color = Color::rgb(0.5, 0.5, 1.0);
     pub const SCORE_COLOR: Color = Color::rgb(1.0, 0.5, 0.5);
 }
 
-pub fn setup_scoreboard(mut commands: Commands) {
+pub fn setup_scoreboard(mut commands: Commands, mode: Res<GameMode>, coop: Res<Coop>) {
     commands.insert_resource(Scoreboard(0));
+    commands.insert_resource(PlayerScores::default());
+
+    if mode.is_versus() {
+        for (i, player) in Player::ALL.into_iter().enumerate() {
+            commands.spawn((
+                VersusScoreboardText(player),
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font_size: Scoreboard::FONT_SIZE * 0.75,
+                            color: player.color(),
+                            ..default()
+                        },
+                    ),
+                    transform: Transform::from_translation(
+                        (camera::WINDOW_TOP_LEFT - Vec2::Y * i as f32 * Scoreboard::FONT_SIZE)
+                            .extend(0.0),
+                    ),
+                    text_anchor: Anchor::TopLeft,
+                    ..default()
+                },
+            ));
+        }
+        return;
+    }
+
+    if coop.enabled {
+        commands.spawn((
+            PlayerScoresText,
+            Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font_size: Scoreboard::FONT_SIZE * 0.75,
+                        color: Scoreboard::TEXT_COLOR,
+                        ..default()
+                    },
+                ),
+                transform: Transform::from_translation(
+                    (camera::WINDOW_TOP_LEFT - Vec2::Y * Scoreboard::FONT_SIZE).extend(0.0),
+                ),
+                text_anchor: Anchor::TopLeft,
+                ..default()
+            },
+        ));
+    }
 
     commands.spawn((
         ScoreboardText,
@@ -44,6 +145,19 @@ pub fn setup_scoreboard(mut commands: Commands) {
                     color: Scoreboard::SCORE_COLOR,
                     ..default()
                 }),
+                TextSection::new(
+                    "  Lives: ",
+                    TextStyle {
+                        font_size: Scoreboard::FONT_SIZE,
+                        color: Scoreboard::TEXT_COLOR,
+                        ..default()
+                    },
+                ),
+                TextSection::from_style(TextStyle {
+                    font_size: Scoreboard::FONT_SIZE,
+                    color: Scoreboard::SCORE_COLOR,
+                    ..default()
+                }),
             ]),
             transform: Transform::from_translation(camera::WINDOW_TOP_LEFT.extend(0.0)),
             text_anchor: Anchor::TopLeft,
@@ -52,8 +166,31 @@ pub fn setup_scoreboard(mut commands: Commands) {
     ));
 }
 
-fn cleanup_scoreboard(mut commands: Commands, query: Query<Entity, With<ScoreboardText>>) {
+#[allow(clippy::type_complexity)]
+pub fn cleanup_scoreboard(
+    mut commands: Commands,
+    score: Res<Scoreboard>,
+    player_scores: Res<PlayerScores>,
+    mode: Res<GameMode>,
+    coop: Res<Coop>,
+    versus: Option<Res<VersusProgress>>,
+    query: Query<
+        Entity,
+        Or<(
+            With<ScoreboardText>,
+            With<PlayerScoresText>,
+            With<VersusScoreboardText>,
+        )>,
+    >,
+) {
+    commands.insert_resource(FinalScore {
+        mode: *mode,
+        score: **score,
+        players: (mode.players(*coop).len() > 1).then_some(*player_scores),
+        winner: versus.and_then(|versus| versus.winner),
+    });
     commands.remove_resource::<Scoreboard>();
+    commands.remove_resource::<PlayerScores>();
 
     for entity in &query {
         commands.entity(entity).despawn();
@@ -62,8 +199,47 @@ fn cleanup_scoreboard(mut commands: Commands, query: Query<Entity, With<Scoreboa
 
 pub fn update_scoreboard(
     score: Res<Scoreboard>,
+    lives: Res<Lives>,
     mut query: Query<&mut Text, With<ScoreboardText>>,
 ) {
-    let mut text = query.single_mut();
+    // Versus games only have the per player scoreboards
+    let Ok(mut text) = query.get_single_mut() else {
+        return;
+    };
     text.sections[1].value = score.to_string();
+    text.sections[3].value = lives.to_string();
+}
+
+fn update_player_scores(
+    player_scores: Res<PlayerScores>,
+    mut query: Query<&mut Text, With<PlayerScoresText>>,
+) {
+    if !player_scores.is_changed() {
+        return;
+    }
+
+    for mut text in &mut query {
+        text.sections[0].value = player_scores.summary();
+    }
+}
+
+fn update_versus_scoreboards(
+    player_scores: Res<PlayerScores>,
+    versus: Option<Res<VersusProgress>>,
+    mut query: Query<(&mut Text, &VersusScoreboardText)>,
+) {
+    let Some(versus) = versus else {
+        return;
+    };
+
+    for (mut text, player) in &mut query {
+        text.sections[0].value = format!(
+            "{}  Score: {}  Health: {}  Garbage: {}/{}",
+            player.name(),
+            player_scores.get(**player),
+            versus.health(**player),
+            versus.garbage(**player),
+            VersusProgress::GARBAGE_BLOCKS
+        );
+    }
 }
@@ -0,0 +1,221 @@
+use bevy::{math::vec2, prelude::*, sprite::Anchor, utils::HashMap};
+
+use crate::{camera, AppState};
+
+use super::{
+    ball::BallLost,
+    block::{BlockDestroyed, BlockHit},
+    boss::{Boss, BossDefeated},
+    check_ball_collision,
+    dmg_text::spawn_score_popup,
+    mutator::Mutators,
+    paddle::PaddleHit,
+    paddle::Player,
+    scoreboard::{PlayerScores, Scoreboard},
+};
+
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Game), setup_combo)
+            .add_systems(OnExit(AppState::Game), cleanup_combo)
+            .add_systems(
+                FixedUpdate,
+                (
+                    update_combo.after(check_ball_collision),
+                    score_destroyed_blocks.after(update_combo),
+                )
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                Update,
+                (decay_multiplier, update_combo_meter).run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+/// Consecutive block hits since a ball last touched the paddle or was lost, and
+/// the score multiplier they build up.
+#[derive(Resource, Clone)]
+pub struct Combo {
+    pub hits: u32,
+    pub longest: u32,
+    pub multiplier: f32,
+}
+
+#[derive(Component, Clone)]
+pub struct ComboMeter;
+
+#[derive(Component, Clone)]
+pub struct ComboBar {
+    max_len: f32,
+}
+
+impl Combo {
+    pub const MULTIPLIER_STEP: f32 = 0.1;
+    pub const MAX_MULTIPLIER: f32 = 5.0;
+    /// Multiplier lost every second
+    pub const DECAY: f32 = 0.25;
+    /// Points for every block after the first one destroyed by a ball in the same hit
+    pub const MULTI_HIT_BONUS: u32 = 50;
+
+    pub const FONT_SIZE: f32 = 30.0;
+    pub const BAR_SIZE: Vec2 = vec2(200.0, 8.0);
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Self {
+            hits: 0,
+            longest: 0,
+            multiplier: 1.0,
+        }
+    }
+}
+
+fn setup_combo(mut commands: Commands) {
+    commands.init_resource::<Combo>();
+
+    let top_right = camera::WINDOW_TOP_RIGHT.extend(0.0);
+
+    commands.spawn((
+        ComboMeter,
+        Text2dBundle {
+            text: Text::from_sections([
+                TextSection::new(
+                    "Combo: ",
+                    TextStyle {
+                        font_size: Combo::FONT_SIZE,
+                        color: Scoreboard::TEXT_COLOR,
+                        ..default()
+                    },
+                ),
+                TextSection::from_style(TextStyle {
+                    font_size: Combo::FONT_SIZE,
+                    color: Scoreboard::SCORE_COLOR,
+                    ..default()
+                }),
+            ]),
+            transform: Transform::from_translation(top_right),
+            text_anchor: Anchor::TopRight,
+            ..default()
+        },
+    ));
+
+    commands.spawn((
+        ComboMeter,
+        ComboBar {
+            max_len: Combo::BAR_SIZE.x,
+        },
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::GOLD,
+                anchor: Anchor::TopRight,
+                custom_size: Some(vec2(0.0, Combo::BAR_SIZE.y)),
+                ..default()
+            },
+            transform: Transform::from_translation(top_right - Vec3::Y * Combo::FONT_SIZE),
+            ..default()
+        },
+    ));
+}
+
+fn cleanup_combo(mut commands: Commands, query: Query<Entity, With<ComboMeter>>) {
+    commands.remove_resource::<Combo>();
+
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn update_combo(
+    mut combo: ResMut<Combo>,
+    mut block_hit: EventReader<BlockHit>,
+    mut paddle_hit: EventReader<PaddleHit>,
+    mut ball_lost: EventReader<BallLost>,
+) {
+    // The combo ends as soon as a ball comes back to a paddle or is lost
+    if paddle_hit.read().count() + ball_lost.read().count() > 0 {
+        combo.hits = 0;
+        combo.multiplier = 1.0;
+    }
+
+    for _ in block_hit.read() {
+        combo.hits += 1;
+        combo.longest = combo.longest.max(combo.hits);
+        combo.multiplier = (combo.multiplier + Combo::MULTIPLIER_STEP).min(Combo::MAX_MULTIPLIER);
+    }
+}
+
+fn score_destroyed_blocks(
+    mut commands: Commands,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut player_scores: ResMut<PlayerScores>,
+    combo: Res<Combo>,
+    mutators: Res<Mutators>,
+    mut block_destroyed: EventReader<BlockDestroyed>,
+    mut boss_defeated: EventReader<BossDefeated>,
+) {
+    let mut per_ball = HashMap::<Entity, (u32, Vec3, Player)>::new();
+    let multiplier = combo.multiplier * mutators.score_multiplier();
+
+    for destroyed in block_destroyed.read() {
+        let points = (destroyed.kind.points() as f32 * multiplier).round() as u32;
+        **scoreboard += points;
+        player_scores.add(destroyed.player, points);
+        spawn_score_popup(&mut commands, destroyed.translation, points);
+
+        let Some(ball) = destroyed.ball else {
+            continue;
+        };
+        let (count, last, player) = per_ball.entry(ball).or_default();
+        *count += 1;
+        *last = destroyed.translation;
+        *player = destroyed.player;
+    }
+
+    for defeated in boss_defeated.read() {
+        let points = (Boss::POINTS as f32 * multiplier).round() as u32;
+        **scoreboard += points;
+        player_scores.add(defeated.player, points);
+        spawn_score_popup(&mut commands, defeated.translation, points);
+    }
+
+    for (count, translation, player) in per_ball.into_values() {
+        if count < 2 {
+            continue;
+        }
+
+        let bonus = (Combo::MULTI_HIT_BONUS as f32 * mutators.score_multiplier()).round() as u32
+            * (count - 1);
+        **scoreboard += bonus;
+        player_scores.add(player, bonus);
+        spawn_score_popup(&mut commands, translation + Vec3::Y * 20.0, bonus);
+    }
+}
+
+fn decay_multiplier(time: Res<Time>, mut combo: ResMut<Combo>) {
+    if combo.multiplier > 1.0 {
+        combo.multiplier = (combo.multiplier - Combo::DECAY * time.delta_seconds()).max(1.0);
+    }
+}
+
+fn update_combo_meter(
+    combo: Res<Combo>,
+    mut text_q: Query<&mut Text, With<ComboMeter>>,
+    mut bar_q: Query<(&mut Sprite, &ComboBar)>,
+) {
+    if !combo.is_changed() {
+        return;
+    }
+
+    let mut text = text_q.single_mut();
+    text.sections[1].value = format!("{} x{:.1}", combo.hits, combo.multiplier);
+
+    let (mut sprite, bar) = bar_q.single_mut();
+    let percentage = (combo.multiplier - 1.0) / (Combo::MAX_MULTIPLIER - 1.0);
+    sprite.custom_size = sprite
+        .custom_size
+        .map(|size| vec2(percentage * bar.max_len, size.y));
+}
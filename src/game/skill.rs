@@ -0,0 +1,432 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+
+use crate::AppState;
+
+use super::{
+    ball::Ball, damage::DamageType, mutator::Mutators, paddle::LevelUp, setup_game, GameState,
+};
+
+pub struct SkillPlugin;
+
+impl Plugin for SkillPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SkillTree>()
+            .init_resource::<Modifiers>()
+            .add_systems(
+                OnEnter(AppState::Game),
+                // The lives of the new game depend on the modifiers
+                (reset_skill_tree, update_modifiers.before(setup_game)).chain(),
+            )
+            .add_systems(OnExit(AppState::Game), stop_leveling_up)
+            .add_systems(OnEnter(GameState::LevelingUp), pause_time)
+            .add_systems(OnExit(GameState::LevelingUp), unpause_time)
+            .add_systems(
+                Update,
+                (grant_skill_points, update_modifiers, apply_affinity)
+                    .run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+/// Game values that can be changed by [Modifiers]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stat {
+    Attack,
+    PaddleSpeed,
+    DashCooldown,
+    PowerupDuration,
+    PowerupChance,
+    CritChance,
+    CritMultiplier,
+    DamageVariance,
+    BallSpeed,
+    /// Balls left after the first one
+    Lives,
+    /// Fraction of the health of a new block randomly added or removed
+    BlockHealthVariance,
+}
+
+/// Game behaviour switched on by [Modifiers], for the changes that are not a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rule {
+    /// Left and right inputs are swapped
+    MirroredControls,
+    /// A boss projectile hitting a paddle ends the run
+    FatalProjectiles,
+    /// Balls shatter after a few block hits
+    ShatteringBalls,
+}
+
+/// Change of a single [Stat]: `(base + flat) * (1 + percent)`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StatModifier {
+    pub flat: f32,
+    pub percent: f32,
+}
+
+impl StatModifier {
+    pub const fn flat(flat: f32) -> Self {
+        Self { flat, percent: 0.0 }
+    }
+
+    pub const fn percent(percent: f32) -> Self {
+        Self { flat: 0.0, percent }
+    }
+}
+
+/// Every active change of the game [Stat]s and [Rule]s. Systems read their values
+/// through [Modifiers::apply] and [Modifiers::has] instead of using the constants
+/// directly.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct Modifiers {
+    stats: BTreeMap<Stat, StatModifier>,
+    rules: BTreeSet<Rule>,
+}
+
+impl Modifiers {
+    pub fn add(&mut self, stat: Stat, modifier: StatModifier) {
+        let entry = self.stats.entry(stat).or_default();
+        entry.flat += modifier.flat;
+        entry.percent += modifier.percent;
+    }
+
+    pub fn apply(&self, stat: Stat, base: f32) -> f32 {
+        let Some(modifier) = self.stats.get(&stat) else {
+            return base;
+        };
+
+        ((base + modifier.flat) * (1.0 + modifier.percent)).max(0.0)
+    }
+
+    pub fn enable(&mut self, rule: Rule) {
+        self.rules.insert(rule);
+    }
+
+    pub fn has(&self, rule: Rule) -> bool {
+        self.rules.contains(&rule)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SkillBranch {
+    Damage,
+    Control,
+    Luck,
+}
+
+impl SkillBranch {
+    pub const ALL: [SkillBranch; 3] =
+        [SkillBranch::Damage, SkillBranch::Control, SkillBranch::Luck];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SkillBranch::Damage => "Damage",
+            SkillBranch::Control => "Control",
+            SkillBranch::Luck => "Luck",
+        }
+    }
+}
+
+/// Passive skill, every rank adds its [Skill::modifier] once. Affinities give
+/// their [DamageType] to the physical balls instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Skill {
+    Sharpness,
+    HeavyHitter,
+    Swiftness,
+    QuickDash,
+    Lasting,
+    Scavenger,
+    Brutality,
+    SteadyHands,
+    KeenEye,
+    FireAffinity,
+    IceAffinity,
+    PoisonAffinity,
+}
+
+impl Skill {
+    /// Ordered by tier then by [SkillBranch], as shown in the skill tree
+    pub const ALL: [Skill; 12] = [
+        Skill::Sharpness,
+        Skill::Swiftness,
+        Skill::Lasting,
+        Skill::HeavyHitter,
+        Skill::QuickDash,
+        Skill::Scavenger,
+        Skill::Brutality,
+        Skill::SteadyHands,
+        Skill::KeenEye,
+        Skill::FireAffinity,
+        Skill::IceAffinity,
+        Skill::PoisonAffinity,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Skill::Sharpness => "Sharpness",
+            Skill::HeavyHitter => "Heavy hitter",
+            Skill::Swiftness => "Swiftness",
+            Skill::QuickDash => "Quick dash",
+            Skill::Lasting => "Lasting",
+            Skill::Scavenger => "Scavenger",
+            Skill::Brutality => "Brutality",
+            Skill::SteadyHands => "Steady hands",
+            Skill::KeenEye => "Keen eye",
+            Skill::FireAffinity => "Fire affinity",
+            Skill::IceAffinity => "Ice affinity",
+            Skill::PoisonAffinity => "Poison affinity",
+        }
+    }
+
+    pub fn max_rank(self) -> u32 {
+        match self {
+            Skill::HeavyHitter => 2,
+            Skill::FireAffinity | Skill::IceAffinity | Skill::PoisonAffinity => 1,
+            _ => 3,
+        }
+    }
+
+    /// Skill that needs at least one rank before this one can be learned
+    pub fn requires(self) -> Option<Skill> {
+        match self {
+            Skill::HeavyHitter => Some(Skill::Sharpness),
+            Skill::QuickDash => Some(Skill::Swiftness),
+            Skill::Scavenger => Some(Skill::Lasting),
+            Skill::Brutality => Some(Skill::HeavyHitter),
+            Skill::SteadyHands => Some(Skill::QuickDash),
+            Skill::KeenEye => Some(Skill::Scavenger),
+            Skill::FireAffinity => Some(Skill::Brutality),
+            Skill::IceAffinity => Some(Skill::SteadyHands),
+            Skill::PoisonAffinity => Some(Skill::KeenEye),
+            _ => None,
+        }
+    }
+
+    pub fn modifier(self) -> Option<(Stat, StatModifier)> {
+        let modifier = match self {
+            Skill::Sharpness => (Stat::Attack, StatModifier::flat(1.0)),
+            Skill::HeavyHitter => (Stat::Attack, StatModifier::percent(0.25)),
+            Skill::Swiftness => (Stat::PaddleSpeed, StatModifier::percent(0.1)),
+            Skill::QuickDash => (Stat::DashCooldown, StatModifier::percent(-0.15)),
+            Skill::Lasting => (Stat::PowerupDuration, StatModifier::percent(0.2)),
+            Skill::Scavenger => (Stat::PowerupChance, StatModifier::percent(0.25)),
+            Skill::Brutality => (Stat::CritMultiplier, StatModifier::flat(0.25)),
+            Skill::SteadyHands => (Stat::DamageVariance, StatModifier::percent(-0.3)),
+            Skill::KeenEye => (Stat::CritChance, StatModifier::flat(0.05)),
+            Skill::FireAffinity | Skill::IceAffinity | Skill::PoisonAffinity => return None,
+        };
+
+        Some(modifier)
+    }
+
+    /// Element given to the physical balls by an affinity
+    pub fn damage_type(self) -> Option<DamageType> {
+        match self {
+            Skill::FireAffinity => Some(DamageType::Fire),
+            Skill::IceAffinity => Some(DamageType::Ice),
+            Skill::PoisonAffinity => Some(DamageType::Poison),
+            _ => None,
+        }
+    }
+}
+
+/// Skills learned during the current run, one point is granted by every [LevelUp].
+/// The co-op players share a single tree: a level up of either one grants a point
+/// to spend for both, and the learned skills apply to every ball and paddle.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SkillTree {
+    pub points: u32,
+    ranks: BTreeMap<Skill, u32>,
+}
+
+impl SkillTree {
+    pub fn rank(&self, skill: Skill) -> u32 {
+        self.ranks.get(&skill).copied().unwrap_or(0)
+    }
+
+    /// A single affinity can be learned per run
+    pub fn can_learn(&self, skill: Skill) -> bool {
+        self.points > 0
+            && self.rank(skill) < skill.max_rank()
+            && skill.requires().map_or(true, |s| self.rank(s) > 0)
+            && (skill.damage_type().is_none() || self.affinity().is_none())
+    }
+
+    /// Element of the learned affinity, if any
+    pub fn affinity(&self) -> Option<DamageType> {
+        self.ranks.keys().find_map(|skill| skill.damage_type())
+    }
+
+    /// Spend a point on `skill`, returns `false` if it can't be learned
+    pub fn learn(&mut self, skill: Skill) -> bool {
+        if !self.can_learn(skill) {
+            return false;
+        }
+
+        self.points -= 1;
+        *self.ranks.entry(skill).or_default() += 1;
+        true
+    }
+
+    /// Returns `true` if a point can be spent on any skill
+    pub fn has_choices(&self) -> bool {
+        Skill::ALL.iter().any(|&s| self.can_learn(s))
+    }
+
+    pub fn modifiers(&self) -> Modifiers {
+        let mut modifiers = Modifiers::default();
+        for (&skill, &rank) in &self.ranks {
+            let Some((stat, modifier)) = skill.modifier() else {
+                continue;
+            };
+            for _ in 0..rank {
+                modifiers.add(stat, modifier);
+            }
+        }
+
+        modifiers
+    }
+}
+
+fn reset_skill_tree(mut commands: Commands) {
+    commands.insert_resource(SkillTree::default());
+}
+
+fn grant_skill_points(
+    mut level_up: EventReader<LevelUp>,
+    mut tree: ResMut<SkillTree>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    let levels = level_up.read().count() as u32;
+    if levels == 0 {
+        return;
+    }
+
+    tree.points += levels;
+    if tree.has_choices() {
+        state.set(GameState::LevelingUp);
+    }
+}
+
+/// The [Modifiers] combine the learned skills and the active [Mutators]
+fn update_modifiers(
+    tree: Res<SkillTree>,
+    mutators: Res<Mutators>,
+    mut modifiers: ResMut<Modifiers>,
+) {
+    if !tree.is_changed() && !mutators.is_changed() {
+        return;
+    }
+
+    *modifiers = tree.modifiers();
+    for (stat, modifier) in mutators.modifiers() {
+        modifiers.add(stat, modifier);
+    }
+    for rule in mutators.rules() {
+        modifiers.enable(rule);
+    }
+}
+
+/// Give the learned affinity to every physical ball, including the new ones
+fn apply_affinity(
+    tree: Res<SkillTree>,
+    mut balls: Query<(&mut DamageType, &mut Sprite), With<Ball>>,
+) {
+    let Some(affinity) = tree.affinity() else {
+        return;
+    };
+
+    for (mut damage_type, mut sprite) in &mut balls {
+        if *damage_type == DamageType::Physical {
+            *damage_type = affinity;
+            sprite.color = affinity.ball_color();
+        }
+    }
+}
+
+fn stop_leveling_up(mut state: ResMut<NextState<GameState>>) {
+    state.set(GameState::Looping);
+}
+
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn unpause_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(points: u32) -> SkillTree {
+        SkillTree {
+            points,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn learn_spends_a_point() {
+        let mut tree = tree(2);
+        assert!(tree.learn(Skill::Sharpness));
+        assert_eq!(tree.points, 1);
+        assert_eq!(tree.rank(Skill::Sharpness), 1);
+    }
+
+    #[test]
+    fn learn_needs_a_point() {
+        let mut tree = tree(0);
+        assert!(!tree.learn(Skill::Sharpness));
+        assert_eq!(tree.rank(Skill::Sharpness), 0);
+    }
+
+    #[test]
+    fn learn_needs_the_required_skill() {
+        let mut tree = tree(2);
+        assert!(!tree.learn(Skill::HeavyHitter));
+        assert_eq!(tree.points, 2);
+
+        assert!(tree.learn(Skill::Sharpness));
+        assert!(tree.learn(Skill::HeavyHitter));
+    }
+
+    #[test]
+    fn learn_stops_at_max_rank() {
+        let skill = Skill::Swiftness;
+        let mut tree = tree(skill.max_rank() + 1);
+        for _ in 0..skill.max_rank() {
+            assert!(tree.learn(skill));
+        }
+
+        assert!(!tree.learn(skill));
+        assert_eq!(tree.points, 1);
+        assert_eq!(tree.rank(skill), skill.max_rank());
+    }
+
+    #[test]
+    fn a_single_affinity_can_be_learned() {
+        let mut tree = tree(2);
+        tree.ranks.insert(Skill::Brutality, 1);
+        tree.ranks.insert(Skill::KeenEye, 1);
+
+        assert!(tree.learn(Skill::FireAffinity));
+        assert_eq!(tree.affinity(), Some(DamageType::Fire));
+        assert!(!tree.learn(Skill::PoisonAffinity));
+        assert_eq!(tree.points, 1);
+    }
+
+    #[test]
+    fn every_rank_adds_its_modifier() {
+        let mut tree = tree(2);
+        tree.learn(Skill::Sharpness);
+        tree.learn(Skill::Sharpness);
+
+        assert_eq!(tree.modifiers().apply(Stat::Attack, 1.0), 3.0);
+        assert_eq!(tree.modifiers().apply(Stat::PaddleSpeed, 1.0), 1.0);
+    }
+}
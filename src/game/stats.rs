@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::AppState;
+
+use super::{
+    ball::BallLost,
+    block::{BlockDamaged, BlockDestroyed, BlockKind},
+    check_ball_collision, check_ball_out_of_bound, check_powerups_collision,
+    level::Level,
+    paddle::{LevelUp, Paddle, PaddleHit},
+    powerup::{PowerupClass, PowerupCollected},
+    scoreboard::Scoreboard,
+    scoring::Combo,
+    setup_game, GameMode,
+};
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Game), setup_stats)
+            .add_systems(
+                OnEnter(AppState::Game),
+                seed_max_level.after(setup_stats).after(setup_game),
+            )
+            .add_systems(OnExit(AppState::Game), export_stats)
+            .add_systems(
+                FixedUpdate,
+                collect_stats
+                    .after(check_ball_collision)
+                    .after(check_ball_out_of_bound)
+                    .after(check_powerups_collision)
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(
+                Update,
+                (track_time, track_level).run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+/// Statistics of the current run, kept after the run ends so that the end
+/// screens can show them.
+#[derive(Resource, Serialize, Clone, Default, Debug)]
+pub struct RunStats {
+    pub blocks_destroyed: BTreeMap<BlockKind, u32>,
+    pub balls_lost: u32,
+    pub powerups_collected: BTreeMap<PowerupClass, u32>,
+    pub paddle_hits: u32,
+    pub longest_combo: u32,
+    pub max_level: u32,
+    pub damage_dealt: u32,
+    pub time_survived: f32,
+}
+
+/// A finished run as it is appended to [RunStats::PATH]
+#[derive(Serialize)]
+struct RunRecord<'a> {
+    timestamp: u64,
+    mode: &'static str,
+    score: u32,
+    stats: &'a RunStats,
+}
+
+impl RunStats {
+    pub const PATH: &'static str = "run_stats.json";
+
+    /// Human readable lines for the end screens
+    pub fn summary(&self) -> Vec<String> {
+        let total_blocks: u32 = self.blocks_destroyed.values().sum();
+        let blocks = self
+            .blocks_destroyed
+            .iter()
+            .map(|(kind, n)| format!("{kind:?} {n}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let powerups = self
+            .powerups_collected
+            .iter()
+            .map(|(class, n)| format!("{class:?} {n}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let secs = self.time_survived as u32;
+
+        vec![
+            format!("Time survived: {}:{:02}", secs / 60, secs % 60),
+            format!("Max level: {}", self.max_level),
+            format!("Blocks destroyed: {total_blocks} ({blocks})"),
+            format!("Damage dealt: {}", self.damage_dealt),
+            format!("Longest combo: {}", self.longest_combo),
+            format!("Paddle hits: {}", self.paddle_hits),
+            format!("Balls lost: {}", self.balls_lost),
+            format!("Powerups: {powerups}"),
+        ]
+    }
+
+    /// Append this run to the JSON array in [RunStats::PATH]
+    fn export(&self, mode: GameMode, score: u32) -> io::Result<()> {
+        let mut runs: Vec<serde_json::Value> = match fs::read_to_string(Self::PATH) {
+            Ok(file) => serde_json::from_str(&file)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |t| t.as_secs());
+        runs.push(serde_json::to_value(RunRecord {
+            timestamp,
+            mode: mode.name(),
+            score,
+            stats: self,
+        })?);
+
+        fs::write(Self::PATH, serde_json::to_string_pretty(&runs)?)
+    }
+}
+
+fn setup_stats(mut commands: Commands) {
+    commands.insert_resource(RunStats::default());
+}
+
+/// Start from the level the paddles spawn with rather than zero
+fn seed_max_level(mut stats: ResMut<RunStats>, query: Query<&Level, With<Paddle>>) {
+    stats.max_level = query
+        .iter()
+        .map(|level| level.level)
+        .max()
+        .unwrap_or_default();
+}
+
+fn export_stats(stats: Res<RunStats>, mode: Res<GameMode>, score: Res<Scoreboard>) {
+    if let Err(err) = stats.export(*mode, **score) {
+        warn!("Could not export run to {}: {err}", RunStats::PATH);
+    }
+}
+
+fn collect_stats(
+    mut stats: ResMut<RunStats>,
+    combo: Res<Combo>,
+    mut block_damaged: EventReader<BlockDamaged>,
+    mut block_destroyed: EventReader<BlockDestroyed>,
+    mut paddle_hit: EventReader<PaddleHit>,
+    mut ball_lost: EventReader<BallLost>,
+    mut collected: EventReader<PowerupCollected>,
+) {
+    for damaged in block_damaged.read() {
+        stats.damage_dealt += damaged.damage;
+    }
+    for destroyed in block_destroyed.read() {
+        *stats.blocks_destroyed.entry(destroyed.kind).or_default() += 1;
+    }
+    for PowerupCollected(class) in collected.read() {
+        *stats.powerups_collected.entry(*class).or_default() += 1;
+    }
+    stats.paddle_hits += paddle_hit.read().count() as u32;
+    stats.balls_lost += ball_lost.read().count() as u32;
+    stats.longest_combo = stats.longest_combo.max(combo.longest);
+}
+
+fn track_time(time: Res<Time>, mut stats: ResMut<RunStats>) {
+    stats.time_survived += time.delta_seconds();
+}
+
+fn track_level(mut stats: ResMut<RunStats>, mut level_up: EventReader<LevelUp>) {
+    for level_up in level_up.read() {
+        stats.max_level = stats.max_level.max(level_up.level);
+    }
+}
@@ -0,0 +1,285 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::AppState;
+
+use super::{
+    block::{Block, BlockDamaged},
+    damage::{scaled_damage, DamageType},
+    damage_blocks,
+    paddle::Player,
+};
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                apply_status_effects.after(damage_blocks),
+                (tick_burn, tick_freeze, tick_poison).before(damage_blocks),
+            )
+                .run_if(in_state(AppState::Game)),
+        )
+        .add_systems(Update, tint_blocks.run_if(in_state(AppState::Game)));
+    }
+}
+
+/// Damage over time, applied by [DamageType::Fire] hits
+#[derive(Component, Clone)]
+pub struct Burn {
+    pub duration: Timer,
+    pub tick: Timer,
+    /// Credited for the damage, from the hit that applied the burn
+    pub player: Option<Player>,
+}
+
+impl Burn {
+    pub const DURATION: Duration = Duration::from_secs(3);
+    pub const TICK: Duration = Duration::from_secs(1);
+    pub const DAMAGE: u32 = 1;
+    pub const TINT: Color = Color::ORANGE_RED;
+
+    fn new(player: Option<Player>) -> Self {
+        Self {
+            duration: Timer::new(Self::DURATION, TimerMode::Once),
+            tick: Timer::new(Self::TICK, TimerMode::Repeating),
+            player,
+        }
+    }
+}
+
+/// The block skips the descents until the timer finishes, applied by
+/// [DamageType::Ice] hits
+#[derive(Component, Clone, Deref, DerefMut)]
+pub struct Frozen(pub Timer);
+
+impl Frozen {
+    pub const DURATION: Duration = Duration::from_secs(12);
+    pub const TINT: Color = Color::rgb(0.6, 0.9, 1.0);
+}
+
+/// Damage over time that spreads to the adjacent blocks, applied by
+/// [DamageType::Poison] hits
+#[derive(Component, Clone)]
+pub struct Poison {
+    pub duration: Timer,
+    pub tick: Timer,
+    /// How many more times the poison can jump to another block
+    pub spreads: u32,
+    /// Credited for the damage, from the hit that applied the poison
+    pub player: Option<Player>,
+}
+
+impl Poison {
+    pub const DURATION: Duration = Duration::from_secs(4);
+    pub const TICK: Duration = Duration::from_secs(1);
+    pub const DAMAGE: u32 = 1;
+    pub const SPREADS: u32 = 2;
+    pub const TINT: Color = Color::DARK_GREEN;
+
+    fn new(spreads: u32, player: Option<Player>) -> Self {
+        Self {
+            duration: Timer::new(Self::DURATION, TimerMode::Once),
+            tick: Timer::new(Self::TICK, TimerMode::Repeating),
+            spreads,
+            player,
+        }
+    }
+}
+
+/// Ball hits apply the status of their [DamageType], refreshing it if already present
+fn apply_status_effects(
+    mut commands: Commands,
+    mut block_damaged: EventReader<BlockDamaged>,
+    blocks: Query<(), With<Block>>,
+) {
+    for damaged in block_damaged.read() {
+        // The bosses are immune to the status effects
+        if damaged.ball.is_none() || !blocks.contains(damaged.block) {
+            continue;
+        }
+
+        // The block may have been destroyed by this same hit
+        let mut block = commands.entity(damaged.block);
+        match damaged.damage_type {
+            DamageType::Physical => {}
+            DamageType::Fire => {
+                block.try_insert(Burn::new(damaged.player));
+            }
+            DamageType::Ice => {
+                block.try_insert(Frozen(Timer::new(Frozen::DURATION, TimerMode::Once)));
+            }
+            DamageType::Poison => {
+                block.try_insert(Poison::new(Poison::SPREADS, damaged.player));
+            }
+        }
+    }
+}
+
+fn tick_burn(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &Transform, &Block, &mut Burn)>,
+    mut block_damaged: EventWriter<BlockDamaged>,
+) {
+    for (entity, transform, block, mut burn) in &mut query {
+        burn.duration.tick(time.delta());
+        burn.tick.tick(time.delta());
+
+        if burn.tick.just_finished() {
+            block_damaged.send(status_damage(
+                entity,
+                transform,
+                block,
+                Burn::DAMAGE,
+                DamageType::Fire,
+                burn.player,
+            ));
+        }
+
+        if burn.duration.finished() {
+            commands.entity(entity).remove::<Burn>();
+        }
+    }
+}
+
+fn tick_freeze(mut commands: Commands, time: Res<Time>, mut query: Query<(Entity, &mut Frozen)>) {
+    for (entity, mut frozen) in &mut query {
+        frozen.tick(time.delta());
+
+        if frozen.finished() {
+            commands.entity(entity).remove::<Frozen>();
+        }
+    }
+}
+
+fn tick_poison(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &Transform, &Block, Option<&mut Poison>)>,
+    mut block_damaged: EventWriter<BlockDamaged>,
+) {
+    let mut spreading = Vec::new();
+
+    for (entity, transform, block, poison) in &mut query {
+        let Some(mut poison) = poison else { continue };
+
+        poison.duration.tick(time.delta());
+        poison.tick.tick(time.delta());
+
+        if poison.tick.just_finished() {
+            block_damaged.send(status_damage(
+                entity,
+                transform,
+                block,
+                Poison::DAMAGE,
+                DamageType::Poison,
+                poison.player,
+            ));
+
+            if poison.spreads > 0 {
+                spreading.push((
+                    transform.translation.truncate(),
+                    poison.spreads - 1,
+                    poison.player,
+                ));
+            }
+        }
+
+        if poison.duration.finished() {
+            commands.entity(entity).remove::<Poison>();
+        }
+    }
+
+    for (origin, spreads, player) in spreading {
+        for (entity, transform, _, poison) in &query {
+            if poison.is_none() && is_adjacent(origin, transform.translation.truncate()) {
+                commands
+                    .entity(entity)
+                    .try_insert(Poison::new(spreads, player));
+            }
+        }
+    }
+}
+
+/// Returns `true` if the blocks at `a` and `b` are side by side in the grid
+fn is_adjacent(a: Vec2, b: Vec2) -> bool {
+    let step = Block::SIZE + Block::PADDING;
+    let d = (a - b).abs();
+
+    (d.x < 1.0 && d.y > 1.0 && d.y < 1.1 * step.y) || (d.y < 1.0 && d.x > 1.0 && d.x < 1.1 * step.x)
+}
+
+fn status_damage(
+    entity: Entity,
+    transform: &Transform,
+    block: &Block,
+    damage: u32,
+    damage_type: DamageType,
+    player: Option<Player>,
+) -> BlockDamaged {
+    let (damage, effectiveness) = scaled_damage(damage, block.kind.damage_multiplier(damage_type));
+
+    BlockDamaged {
+        block: entity,
+        ball: None,
+        player,
+        damage,
+        crit: false,
+        damage_type,
+        effectiveness,
+        translation: transform.translation,
+    }
+}
+
+/// Blocks under a status are tinted with its color, burn shows over poison over freeze
+#[allow(clippy::type_complexity)]
+fn tint_blocks(mut query: Query<(&mut Sprite, &Block, Has<Burn>, Has<Frozen>, Has<Poison>)>) {
+    for (mut sprite, block, burning, frozen, poisoned) in &mut query {
+        sprite.color = if burning {
+            Burn::TINT
+        } else if poisoned {
+            Poison::TINT
+        } else if frozen {
+            Frozen::TINT
+        } else {
+            block.kind.color()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::vec2;
+
+    use super::*;
+
+    const STEP: Vec2 = vec2(
+        Block::SIZE.x + Block::PADDING,
+        Block::SIZE.y + Block::PADDING,
+    );
+
+    #[test]
+    fn side_by_side_blocks_are_adjacent() {
+        let a = vec2(10.0, 20.0);
+        assert!(is_adjacent(a, a + vec2(STEP.x, 0.0)));
+        assert!(is_adjacent(a, a - vec2(STEP.x, 0.0)));
+        assert!(is_adjacent(a, a + vec2(0.0, STEP.y)));
+        assert!(is_adjacent(a, a - vec2(0.0, STEP.y)));
+    }
+
+    #[test]
+    fn a_block_is_not_adjacent_to_itself() {
+        assert!(!is_adjacent(Vec2::ZERO, Vec2::ZERO));
+    }
+
+    #[test]
+    fn diagonal_and_distant_blocks_are_not_adjacent() {
+        assert!(!is_adjacent(Vec2::ZERO, STEP));
+        assert!(!is_adjacent(Vec2::ZERO, vec2(2.0 * STEP.x, 0.0)));
+        assert!(!is_adjacent(Vec2::ZERO, vec2(0.0, 2.0 * STEP.y)));
+    }
+}
@@ -0,0 +1,278 @@
+use std::fs;
+use std::time::{Duration, SystemTime};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::ball::BallPhysics;
+use super::paddle::PaddleMovement;
+
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tuning>()
+            .init_resource::<TuningWatcher>()
+            .add_systems(Startup, load_tuning)
+            .add_systems(Update, (reload_tuning, apply_tuning.after(reload_tuning)));
+    }
+}
+
+/// Game balance, read from [Tuning::PATH] at startup and reloaded whenever the
+/// file changes. Speeds and timers apply right away, the ball speed from the
+/// next lost ball. The descent timer is also scaled by the
+/// [Difficulty](super::Difficulty).
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Tuning {
+    pub ball_speed: f32,
+    /// Balls bounce on each other instead of passing through
+    pub ball_collisions: bool,
+    pub paddle_speed: f32,
+    /// Accelerate and slow down with friction instead of moving at constant speed
+    pub paddle_inertia: bool,
+    pub paddle_acceleration: f32,
+    pub paddle_friction: f32,
+    pub dash_speed: f32,
+    /// Seconds
+    pub dash_duration: f32,
+    /// Seconds
+    pub dash_cooldown: f32,
+    pub powerup_speed: f32,
+    /// Exp needed for the first level up
+    pub level_cap: u32,
+    /// Growth of the exp needed by every following level
+    pub level_up_multiplier: f32,
+    /// Seconds between two descents of the blocks
+    pub block_go_down_timeout: f32,
+    /// Descents before a new row of blocks appears
+    pub block_spawn_after_go_down: u32,
+    /// Seconds
+    pub ball_enlarge_timeout: f32,
+    /// Seconds
+    pub paddle_resize_timeout: f32,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            ball_speed: 400.0,
+            ball_collisions: true,
+            paddle_speed: 500.0,
+            paddle_inertia: false,
+            paddle_acceleration: 3000.0,
+            paddle_friction: 2500.0,
+            dash_speed: 1500.0,
+            dash_duration: 0.12,
+            dash_cooldown: 1.5,
+            powerup_speed: 50.0,
+            level_cap: 50,
+            level_up_multiplier: 1.5,
+            block_go_down_timeout: 10.0,
+            block_spawn_after_go_down: 2,
+            ball_enlarge_timeout: 10.0,
+            paddle_resize_timeout: 10.0,
+        }
+    }
+}
+
+impl Tuning {
+    pub const PATH: &'static str = "assets/tuning.json";
+
+    /// Parse and [validate](Tuning::validate) the file, every problem found is
+    /// returned as a separate message.
+    pub fn load() -> Result<Self, Vec<String>> {
+        let file = fs::read_to_string(Self::PATH).map_err(|err| vec![err.to_string()])?;
+        let tuning: Self = serde_json::from_str(&file).map_err(|err| vec![err.to_string()])?;
+
+        let errors = tuning.validate();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(tuning)
+    }
+
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut positive = |name: &str, value: f32| {
+            if !(value > 0.0 && value.is_finite()) {
+                errors.push(format!("`{name}` must be a positive number, got {value}"));
+            }
+        };
+
+        positive("ball_speed", self.ball_speed);
+        positive("paddle_speed", self.paddle_speed);
+        positive("paddle_acceleration", self.paddle_acceleration);
+        positive("paddle_friction", self.paddle_friction);
+        positive("dash_speed", self.dash_speed);
+        positive("dash_duration", self.dash_duration);
+        positive("dash_cooldown", self.dash_cooldown);
+        positive("powerup_speed", self.powerup_speed);
+        positive("block_go_down_timeout", self.block_go_down_timeout);
+        positive("ball_enlarge_timeout", self.ball_enlarge_timeout);
+        positive("paddle_resize_timeout", self.paddle_resize_timeout);
+
+        if self.level_cap == 0 {
+            errors.push("`level_cap` must be at least 1".to_string());
+        }
+        if !(self.level_up_multiplier >= 0.0 && self.level_up_multiplier.is_finite()) {
+            errors.push(format!(
+                "`level_up_multiplier` must be zero or positive, got {}",
+                self.level_up_multiplier
+            ));
+        }
+        if self.block_spawn_after_go_down == 0 {
+            errors.push("`block_spawn_after_go_down` must be at least 1".to_string());
+        }
+
+        errors
+    }
+
+    pub fn level_exp_cap(&self, level: u32) -> u32 {
+        (self.level_cap as f32 * (1. + level as f32 * self.level_up_multiplier)) as u32
+    }
+
+    pub fn block_go_down_timeout(&self) -> Duration {
+        Duration::from_secs_f32(self.block_go_down_timeout)
+    }
+}
+
+/// Last modification seen of the [Tuning] file, checked every [TuningWatcher::INTERVAL]
+#[derive(Resource)]
+struct TuningWatcher {
+    modified: Option<SystemTime>,
+    timer: Timer,
+}
+
+impl TuningWatcher {
+    const INTERVAL: Duration = Duration::from_secs(1);
+}
+
+impl Default for TuningWatcher {
+    fn default() -> Self {
+        Self {
+            modified: None,
+            timer: Timer::new(Self::INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+fn file_modified() -> Option<SystemTime> {
+    fs::metadata(Tuning::PATH).and_then(|m| m.modified()).ok()
+}
+
+fn report_errors(errors: &[String]) {
+    error!(
+        "Invalid {}, keeping the previous values:\n  {}",
+        Tuning::PATH,
+        errors.join("\n  ")
+    );
+}
+
+fn load_tuning(mut tuning: ResMut<Tuning>, mut watcher: ResMut<TuningWatcher>) {
+    watcher.modified = file_modified();
+
+    match Tuning::load() {
+        Ok(loaded) => *tuning = loaded,
+        Err(errors) => report_errors(&errors),
+    }
+}
+
+fn reload_tuning(
+    time: Res<Time<Real>>,
+    mut tuning: ResMut<Tuning>,
+    mut watcher: ResMut<TuningWatcher>,
+) {
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let modified = file_modified();
+    if modified == watcher.modified {
+        return;
+    }
+    watcher.modified = modified;
+
+    match Tuning::load() {
+        Ok(loaded) => {
+            info!("Reloaded {}", Tuning::PATH);
+            tuning.set_if_neq(loaded);
+        }
+        Err(errors) => report_errors(&errors),
+    }
+}
+
+/// Push the values that live in other resources
+fn apply_tuning(
+    tuning: Res<Tuning>,
+    mut movement: ResMut<PaddleMovement>,
+    mut physics: ResMut<BallPhysics>,
+) {
+    if tuning.is_changed() {
+        physics.ball_collisions = tuning.ball_collisions;
+        movement.inertia = tuning.paddle_inertia;
+        movement.top_speed = tuning.paddle_speed;
+        movement.acceleration = tuning.paddle_acceleration;
+        movement.friction = tuning.paddle_friction;
+        movement.dash_speed = tuning.dash_speed;
+        movement.dash_duration = tuning.dash_duration;
+        movement.dash_cooldown = tuning.dash_cooldown;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_tuning_is_valid() {
+        assert_eq!(Tuning::default().validate(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn tuning_file_matches_the_defaults() {
+        let file = fs::read_to_string(Tuning::PATH).unwrap();
+        let tuning: Tuning = serde_json::from_str(&file).unwrap();
+        assert_eq!(tuning, Tuning::default());
+    }
+
+    #[test]
+    fn every_invalid_value_is_reported() {
+        let tuning = Tuning {
+            ball_speed: 0.0,
+            dash_cooldown: f32::NAN,
+            level_cap: 0,
+            level_up_multiplier: -1.0,
+            block_spawn_after_go_down: 0,
+            ..default()
+        };
+
+        let errors = tuning.validate();
+        assert_eq!(errors.len(), 5, "{errors:?}");
+        for name in [
+            "ball_speed",
+            "dash_cooldown",
+            "level_cap",
+            "level_up_multiplier",
+            "block_spawn_after_go_down",
+        ] {
+            assert!(
+                errors.iter().any(|e| e.contains(&format!("`{name}`"))),
+                "{name} not reported in {errors:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn level_exp_cap_grows_with_the_level() {
+        let tuning = Tuning {
+            level_cap: 50,
+            level_up_multiplier: 1.5,
+            ..default()
+        };
+
+        assert_eq!(tuning.level_exp_cap(0), 50);
+        assert_eq!(tuning.level_exp_cap(2), 200);
+    }
+}
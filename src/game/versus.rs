@@ -0,0 +1,321 @@
+use bevy::{math::vec2, prelude::*};
+use rand::Rng;
+
+use crate::AppState;
+
+use super::{
+    ball::{progress_ball_speed, Ball, BallBundle, BallLost},
+    block::{Block, BlockBundle, BlockDestroyed, BlockKind},
+    block_translation, check_ball_out_of_bound, damage_blocks,
+    paddle::{Paddle, Player},
+    rng::GameRng,
+    scoreboard::cleanup_scoreboard,
+    GameMode, MainBox, Velocity,
+};
+
+/// Rules of [GameMode::Versus]: every player defends an edge behind a block
+/// wall, and the blocks they destroy come back as garbage rows on the wall of
+/// their opponent.
+pub struct VersusPlugin;
+
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Game), setup_versus.run_if(is_versus))
+            .add_systems(
+                OnExit(AppState::Game),
+                cleanup_versus.after(cleanup_scoreboard),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    damage_sides.after(check_ball_out_of_bound),
+                    serve_ball.after(damage_sides).after(progress_ball_speed),
+                    send_garbage.after(damage_blocks),
+                    check_buried_walls.after(send_garbage),
+                )
+                    .run_if(in_state(AppState::Game))
+                    .run_if(is_versus),
+            );
+    }
+}
+
+/// Player whose wall a block is part of
+#[derive(Component, Clone, Copy, Debug, Deref)]
+pub struct WallSide(pub Player);
+
+/// State of the current versus game
+#[derive(Resource, Debug, Clone)]
+pub struct VersusProgress {
+    /// Balls each [Player] can still let past their edge
+    pub health: [u32; Player::ALL.len()],
+    /// Blocks destroyed by each [Player] towards their next garbage row
+    pub garbage: [u32; Player::ALL.len()],
+    pub winner: Option<Player>,
+    /// Player the next ball is served towards
+    pub serve: Player,
+}
+
+impl Default for VersusProgress {
+    fn default() -> Self {
+        Self {
+            health: [Self::HEALTH; Player::ALL.len()],
+            garbage: [0; Player::ALL.len()],
+            winner: None,
+            serve: Player::One,
+        }
+    }
+}
+
+impl VersusProgress {
+    pub const HEALTH: u32 = 5;
+    /// Blocks to destroy to send a garbage row to the opponent
+    pub const GARBAGE_BLOCKS: u32 = 6;
+    /// Rows of the wall every player starts with
+    pub const WALL_ROWS: i32 = 3;
+    /// Empty rows between a starting wall and its paddle
+    pub const PADDLE_GAP_ROWS: i32 = 2;
+
+    pub fn health(&self, player: Player) -> u32 {
+        self.health[player.index()]
+    }
+
+    pub fn garbage(&self, player: Player) -> u32 {
+        self.garbage[player.index()]
+    }
+
+    /// A ball got past the paddle of `defender`, returns the winner if it was
+    /// their last health
+    pub fn lose_ball(&mut self, defender: Player) -> Option<Player> {
+        let health = &mut self.health[defender.index()];
+        *health = health.saturating_sub(1);
+        self.serve = defender;
+
+        if self.health(defender) == 0 && self.winner.is_none() {
+            self.winner = Some(defender.opponent());
+            return self.winner;
+        }
+
+        None
+    }
+
+    /// Count a block destroyed by `player`, returns `true` if it completes a
+    /// garbage row for their opponent
+    pub fn destroy_block(&mut self, player: Player) -> bool {
+        let garbage = &mut self.garbage[player.index()];
+        *garbage += 1;
+
+        if *garbage < Self::GARBAGE_BLOCKS {
+            return false;
+        }
+
+        *garbage = 0;
+        true
+    }
+}
+
+/// Distance between two rows of blocks
+const ROW: f32 = Block::SIZE.y + Block::PADDING;
+
+/// Distance from the center of the row of a wall closest to the center, where
+/// the garbage rows come in
+fn front_row_distance() -> f32 {
+    let rows = VersusProgress::WALL_ROWS + VersusProgress::PADDLE_GAP_ROWS;
+    Paddle::START.y.abs() - rows as f32 * ROW
+}
+
+/// A wall with a block this far from the center reached its paddle
+fn buried_distance() -> f32 {
+    Paddle::START.y.abs() - 0.5 * (Paddle::SIZE.y + Block::SIZE.y)
+}
+
+/// Block of the wall of `player` in column `col`, `distance` away from the center
+fn wall_block(
+    main_box: MainBox,
+    player: Player,
+    col: i32,
+    distance: f32,
+    kind: BlockKind,
+) -> (BlockBundle, WallSide) {
+    let mut translation = block_translation(main_box, col, 0);
+    translation.y = player.versus_edge() * distance;
+
+    (
+        BlockBundle::from_kind_translation(kind, translation),
+        WallSide(player),
+    )
+}
+
+pub fn is_versus(mode: Res<GameMode>) -> bool {
+    mode.is_versus()
+}
+
+/// Spawn the starting wall in front of every player
+pub fn spawn_versus_walls(commands: &mut Commands, main_box: MainBox, rng: &mut impl Rng) {
+    for player in Player::ALL {
+        for row in 0..VersusProgress::WALL_ROWS {
+            let distance = front_row_distance() + row as f32 * ROW;
+            for col in 0..Block::WIDTH {
+                commands.spawn(wall_block(
+                    main_box,
+                    player,
+                    col,
+                    distance,
+                    BlockKind::next_rng(rng),
+                ));
+            }
+        }
+    }
+}
+
+fn setup_versus(mut commands: Commands) {
+    commands.insert_resource(VersusProgress::default());
+}
+
+fn cleanup_versus(mut commands: Commands) {
+    commands.remove_resource::<VersusProgress>();
+}
+
+/// Balls getting past a paddle cost its player some health
+fn damage_sides(
+    mut progress: ResMut<VersusProgress>,
+    mut state: ResMut<NextState<AppState>>,
+    mut ball_lost: EventReader<BallLost>,
+) {
+    for defender in ball_lost.read().filter_map(|lost| lost.defender) {
+        if progress.lose_ball(defender).is_some() {
+            state.set(AppState::GameWon);
+        }
+    }
+}
+
+/// Serve a new ball from the center once every ball is lost
+fn serve_ball(mut commands: Commands, progress: Res<VersusProgress>, query: Query<(), With<Ball>>) {
+    if !query.is_empty() {
+        return;
+    }
+
+    let direction = vec2(
+        Ball::DIRECTION.x,
+        Ball::DIRECTION.y.abs() * progress.serve.versus_edge(),
+    );
+    commands.spawn(BallBundle::from_trans_vel(
+        Transform::from_translation(vec2(Ball::START.x, 0.0).extend(0.0)),
+        Velocity(direction),
+    ));
+}
+
+/// Turn every [VersusProgress::GARBAGE_BLOCKS] destroyed blocks into a row
+/// pushing the wall of the opponent towards their edge
+fn send_garbage(
+    mut commands: Commands,
+    main_box: Res<MainBox>,
+    mut progress: ResMut<VersusProgress>,
+    mut rng: ResMut<GameRng>,
+    mut block_destroyed: EventReader<BlockDestroyed>,
+    mut walls: Query<(&mut Transform, &WallSide), With<Block>>,
+) {
+    let mut rows = [0; Player::ALL.len()];
+    for destroyed in block_destroyed.read() {
+        if progress.destroy_block(destroyed.player) {
+            rows[destroyed.player.opponent().index()] += 1;
+        }
+    }
+
+    for player in Player::ALL {
+        let rows = rows[player.index()];
+        if rows == 0 {
+            continue;
+        }
+
+        for (mut transform, _) in walls.iter_mut().filter(|(_, side)| ***side == player) {
+            transform.translation.y += player.versus_edge() * rows as f32 * ROW;
+        }
+
+        // Every garbage row has a single gap
+        for row in 0..rows {
+            let distance = front_row_distance() + row as f32 * ROW;
+            let gap = rng.field.gen_range(0..Block::WIDTH);
+            for col in (0..Block::WIDTH).filter(|&col| col != gap) {
+                commands.spawn(wall_block(
+                    *main_box,
+                    player,
+                    col,
+                    distance,
+                    BlockKind::Basic,
+                ));
+            }
+        }
+    }
+}
+
+/// A player whose wall was pushed back to their paddle loses
+fn check_buried_walls(
+    mut progress: ResMut<VersusProgress>,
+    mut state: ResMut<NextState<AppState>>,
+    walls: Query<(&Transform, &WallSide), With<Block>>,
+) {
+    if progress.winner.is_some() {
+        return;
+    }
+
+    let buried = walls.iter().find(|(transform, side)| {
+        transform.translation.y * side.versus_edge() >= buried_distance()
+    });
+
+    if let Some((_, side)) = buried {
+        progress.winner = Some(side.opponent());
+        state.set(AppState::GameWon);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_few_blocks_send_a_garbage_row() {
+        let mut progress = VersusProgress::default();
+        for _ in 1..VersusProgress::GARBAGE_BLOCKS {
+            assert!(!progress.destroy_block(Player::One));
+        }
+        assert!(progress.destroy_block(Player::One));
+        assert_eq!(progress.garbage(Player::One), 0);
+    }
+
+    #[test]
+    fn garbage_is_counted_per_player() {
+        let mut progress = VersusProgress::default();
+        for _ in 1..VersusProgress::GARBAGE_BLOCKS {
+            progress.destroy_block(Player::One);
+        }
+
+        assert!(!progress.destroy_block(Player::Two));
+        assert_eq!(
+            progress.garbage(Player::One),
+            VersusProgress::GARBAGE_BLOCKS - 1
+        );
+        assert_eq!(progress.garbage(Player::Two), 1);
+    }
+
+    #[test]
+    fn losing_a_ball_costs_health_and_gets_the_serve() {
+        let mut progress = VersusProgress::default();
+        assert_eq!(progress.lose_ball(Player::Two), None);
+        assert_eq!(progress.health(Player::Two), VersusProgress::HEALTH - 1);
+        assert_eq!(progress.health(Player::One), VersusProgress::HEALTH);
+        assert_eq!(progress.serve, Player::Two);
+    }
+
+    #[test]
+    fn last_health_gives_the_win_to_the_opponent_once() {
+        let mut progress = VersusProgress::default();
+        for _ in 1..VersusProgress::HEALTH {
+            progress.lose_ball(Player::One);
+        }
+
+        assert_eq!(progress.lose_ball(Player::One), Some(Player::Two));
+        assert_eq!(progress.lose_ball(Player::One), None);
+        assert_eq!(progress.winner, Some(Player::Two));
+        assert_eq!(progress.health(Player::One), 0);
+    }
+}
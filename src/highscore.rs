@@ -0,0 +1,339 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::{FinalScore, GameMode};
+use crate::AppState;
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_high_scores)
+            .add_systems(OnEnter(AppState::GameOver), setup_name_entry)
+            .add_systems(OnEnter(AppState::GameWon), setup_name_entry)
+            .add_systems(OnExit(AppState::GameOver), cleanup_name_entry)
+            .add_systems(OnExit(AppState::GameWon), cleanup_name_entry)
+            .add_systems(
+                Update,
+                type_initials.run_if(
+                    resource_exists::<NameEntry>.and_then(
+                        in_state(AppState::GameOver).or_else(in_state(AppState::GameWon)),
+                    ),
+                ),
+            )
+            .add_systems(OnEnter(AppState::HighScores), setup_high_scores)
+            .add_systems(OnExit(AppState::HighScores), cleanup_high_scores)
+            .add_systems(Update, back_to_menu.run_if(in_state(AppState::HighScores)));
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: u32,
+}
+
+/// Top [HighScores::TOP_N] scores of every [GameMode], saved in [HighScores::PATH].
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Default)]
+pub struct HighScores {
+    pub tables: BTreeMap<String, Vec<HighScoreEntry>>,
+}
+
+impl HighScores {
+    pub const PATH: &'static str = "highscores.json";
+    pub const TOP_N: usize = 10;
+    pub const INITIALS_LEN: usize = 3;
+
+    pub fn load() -> io::Result<Self> {
+        let file = match fs::read_to_string(Self::PATH) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err),
+        };
+
+        serde_json::from_str(&file).map_err(io::Error::from)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let file = serde_json::to_string_pretty(self)?;
+        fs::write(Self::PATH, file)
+    }
+
+    pub fn table(&self, mode: GameMode) -> &[HighScoreEntry] {
+        self.tables.get(mode.name()).map_or(&[], |t| t.as_slice())
+    }
+
+    /// Returns `true` if `score` would enter the table of `mode`, versus
+    /// games have no table.
+    pub fn qualifies(&self, mode: GameMode, score: u32) -> bool {
+        let table = self.table(mode);
+        GameMode::SOLO.contains(&mode)
+            && score > 0
+            && (table.len() < Self::TOP_N || table.iter().any(|e| score > e.score))
+    }
+
+    pub fn insert(&mut self, mode: GameMode, entry: HighScoreEntry) {
+        let table = self.tables.entry(mode.name().to_string()).or_default();
+        let index = table.partition_point(|e| e.score >= entry.score);
+        table.insert(index, entry);
+        table.truncate(Self::TOP_N);
+    }
+}
+
+/// Initials being typed on the end screen, present only while the prompt is open.
+#[derive(Resource)]
+pub struct NameEntry {
+    initials: String,
+    root: Entity,
+    text: Entity,
+}
+
+#[derive(Resource, Clone, Copy)]
+struct HighScoresScene {
+    root: Entity,
+}
+
+fn load_high_scores(mut commands: Commands) {
+    let high_scores = HighScores::load().unwrap_or_else(|err| {
+        warn!("Could not load {}: {err}", HighScores::PATH);
+        HighScores::default()
+    });
+
+    commands.insert_resource(high_scores);
+}
+
+fn text_style() -> TextStyle {
+    TextStyle {
+        font_size: 40.0,
+        color: Color::rgb(0.8, 0.8, 0.8),
+        ..default()
+    }
+}
+
+fn setup_name_entry(
+    mut commands: Commands,
+    high_scores: Res<HighScores>,
+    final_score: Option<Res<FinalScore>>,
+) {
+    let Some(final_score) = final_score else {
+        return;
+    };
+    if !high_scores.qualifies(final_score.mode, final_score.score) {
+        return;
+    }
+
+    let mut text = Entity::PLACEHOLDER;
+    let root = commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                bottom: Val::Px(40.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            text = parent
+                .spawn(TextBundle::from_sections([
+                    TextSection::new("New high score! Initials: ", text_style()),
+                    TextSection::new("_".repeat(HighScores::INITIALS_LEN), text_style()),
+                ]))
+                .id();
+        })
+        .id();
+
+    commands.insert_resource(NameEntry {
+        initials: String::new(),
+        root,
+        text,
+    });
+}
+
+fn type_initials(
+    mut commands: Commands,
+    mut events: EventReader<KeyboardInput>,
+    mut entry: ResMut<NameEntry>,
+    mut high_scores: ResMut<HighScores>,
+    final_score: Res<FinalScore>,
+    mut query: Query<&mut Text>,
+) {
+    for event in events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &event.logical_key {
+            Key::Character(c) => {
+                let Some(c) = c.chars().next().filter(char::is_ascii_alphabetic) else {
+                    continue;
+                };
+                if entry.initials.len() < HighScores::INITIALS_LEN {
+                    entry.initials.push(c.to_ascii_uppercase());
+                }
+            }
+            Key::Backspace => {
+                entry.initials.pop();
+            }
+            Key::Enter if !entry.initials.is_empty() => {
+                high_scores.insert(
+                    final_score.mode,
+                    HighScoreEntry {
+                        name: entry.initials.clone(),
+                        score: final_score.score,
+                    },
+                );
+                if let Err(err) = high_scores.save() {
+                    warn!("Could not save {}: {err}", HighScores::PATH);
+                }
+
+                commands.entity(entry.root).despawn_recursive();
+                commands.remove_resource::<NameEntry>();
+                return;
+            }
+            _ => continue,
+        }
+
+        if let Ok(mut text) = query.get_mut(entry.text) {
+            text.sections[1].value = format!(
+                "{:_<width$}",
+                entry.initials,
+                width = HighScores::INITIALS_LEN
+            );
+        }
+    }
+}
+
+fn cleanup_name_entry(mut commands: Commands, entry: Option<Res<NameEntry>>) {
+    let Some(entry) = entry else { return };
+
+    commands.entity(entry.root).despawn_recursive();
+    commands.remove_resource::<NameEntry>();
+}
+
+fn setup_high_scores(mut commands: Commands, high_scores: Res<HighScores>) {
+    let root = commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(80.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for mode in GameMode::SOLO {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(mode.name(), text_style()));
+
+                        let table = high_scores.table(mode);
+                        if table.is_empty() {
+                            parent.spawn(TextBundle::from_section("No scores yet", text_style()));
+                        }
+
+                        for (i, entry) in table.iter().enumerate() {
+                            parent.spawn(TextBundle::from_section(
+                                format!("{:>2}. {:<3} {:>6}", i + 1, entry.name, entry.score),
+                                text_style(),
+                            ));
+                        }
+                    });
+            }
+        })
+        .id();
+
+    commands.insert_resource(HighScoresScene { root });
+}
+
+fn back_to_menu(input: Res<ButtonInput<KeyCode>>, mut state: ResMut<NextState<AppState>>) {
+    if input.just_pressed(KeyCode::Enter) {
+        state.set(AppState::MainMenu);
+    }
+}
+
+fn cleanup_high_scores(mut commands: Commands, scene: Res<HighScoresScene>) {
+    commands.entity(scene.root).despawn_recursive();
+    commands.remove_resource::<HighScoresScene>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: u32) -> HighScoreEntry {
+        HighScoreEntry {
+            name: name.to_string(),
+            score,
+        }
+    }
+
+    fn full_table(mode: GameMode) -> HighScores {
+        let mut high_scores = HighScores::default();
+        for score in 1..=HighScores::TOP_N as u32 {
+            high_scores.insert(mode, entry("AAA", score * 10));
+        }
+        high_scores
+    }
+
+    #[test]
+    fn empty_table_takes_any_positive_score() {
+        let high_scores = HighScores::default();
+        assert!(high_scores.qualifies(GameMode::Classic, 1));
+        assert!(!high_scores.qualifies(GameMode::Classic, 0));
+    }
+
+    #[test]
+    fn full_table_needs_to_beat_an_entry() {
+        let high_scores = full_table(GameMode::Classic);
+        assert!(!high_scores.qualifies(GameMode::Classic, 10));
+        assert!(high_scores.qualifies(GameMode::Classic, 11));
+        assert!(high_scores.qualifies(GameMode::Endless, 10));
+    }
+
+    #[test]
+    fn versus_has_no_high_scores() {
+        assert!(!HighScores::default().qualifies(GameMode::Versus, 100));
+    }
+
+    #[test]
+    fn insert_keeps_tables_sorted_and_truncated() {
+        let mut high_scores = full_table(GameMode::Classic);
+        high_scores.insert(GameMode::Classic, entry("BBB", 55));
+
+        let table = high_scores.table(GameMode::Classic);
+        assert_eq!(table.len(), HighScores::TOP_N);
+        assert!(table.windows(2).all(|w| w[0].score >= w[1].score));
+        assert_eq!(table.last().unwrap().score, 20);
+        assert!(table.contains(&entry("BBB", 55)));
+    }
+
+    #[test]
+    fn insert_puts_ties_after_older_entries() {
+        let mut high_scores = HighScores::default();
+        high_scores.insert(GameMode::Puzzle, entry("OLD", 30));
+        high_scores.insert(GameMode::Puzzle, entry("NEW", 30));
+
+        let table = high_scores.table(GameMode::Puzzle);
+        assert_eq!(table[0], entry("OLD", 30));
+        assert_eq!(table[1], entry("NEW", 30));
+    }
+}
@@ -1,15 +1,17 @@
 use bevy::prelude::*;
 use camera::*;
+use daily::DailyPlugin;
 use game::GamePlugin;
-use game_over::GameOverPlugin;
-use game_won::GameWonPlugin;
+use highscore::HighScorePlugin;
 use menu::*;
+use meta::MetaPlugin;
 
 mod camera;
+mod daily;
 mod game;
-mod game_over;
-mod game_won;
+mod highscore;
 mod menu;
+mod meta;
 
 #[derive(Debug, States, Default, Clone, Eq, PartialEq, Hash)]
 pub enum AppState {
@@ -18,6 +20,9 @@ pub enum AppState {
     Game,
     GameOver,
     GameWon,
+    HighScores,
+    Upgrades,
+    Mutators,
 }
 
 fn main() {
@@ -38,8 +43,14 @@ fn main() {
         .init_state::<AppState>()
         .add_plugins((
             GamePlugin,
-            GameOverPlugin,
-            GameWonPlugin,
+            EndScreenPlugin::new(AppState::GameOver, "You Lost!"),
+            EndScreenPlugin::new(AppState::GameWon, "You Won Nothing!"),
+            HighScorePlugin,
+            DailyPlugin,
+            MetaPlugin,
+            UpgradesPlugin,
+            MutatorsPlugin,
+            SkillTreePlugin,
             MenuPlugin,
             CameraPlugin,
         ))
@@ -1,8 +1,6 @@
 use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
 use bevy::prelude::*;
 
-pub(super) const COLUMN_STYLE: Style = Style::DEFAULT;
-
 #[derive(Component, Default, Debug)]
 pub(crate) struct ScrollingList {
     pub(crate) position: f32,
@@ -11,8 +9,99 @@ pub(crate) struct ScrollingList {
 #[derive(Component, Debug)]
 pub(crate) struct SelectedItem {
     pub(crate) selected: bool,
-    pub(crate) prev: Entity,
-    pub(crate) next: Entity,
+    pub(crate) scope: Entity,
+}
+
+/// Container that owns the focus of its items. Items are laid out row by row,
+/// `columns` per row: a column has `columns == 1`, a row has `columns == items.len()`.
+/// Only the `active` scope reacts to the arrow keys, every other scope keeps its
+/// own focused item untouched.
+#[derive(Component, Debug)]
+pub(crate) struct FocusScope {
+    pub(crate) items: Vec<Entity>,
+    pub(crate) focused: usize,
+    pub(crate) columns: usize,
+    pub(crate) active: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FocusDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl FocusDirection {
+    fn from_input(input: &ButtonInput<KeyCode>) -> Option<Self> {
+        if input.just_pressed(KeyCode::ArrowUp) {
+            Some(Self::Up)
+        } else if input.just_pressed(KeyCode::ArrowDown) {
+            Some(Self::Down)
+        } else if input.just_pressed(KeyCode::ArrowLeft) {
+            Some(Self::Left)
+        } else if input.just_pressed(KeyCode::ArrowRight) {
+            Some(Self::Right)
+        } else {
+            None
+        }
+    }
+}
+
+impl FocusScope {
+    pub(crate) fn new(items: Vec<Entity>, columns: usize) -> Self {
+        Self {
+            items,
+            focused: 0,
+            columns: columns.max(1),
+            active: false,
+        }
+    }
+
+    pub(crate) fn focused_item(&self) -> Option<Entity> {
+        self.items.get(self.focused).copied()
+    }
+
+    /// Index of the item reached moving from the focused one in `direction`,
+    /// wrapping around the current row or column. `None` if the scope is empty.
+    fn neighbour(&self, direction: FocusDirection) -> Option<usize> {
+        let len = self.items.len();
+        if len == 0 {
+            return None;
+        }
+        let columns = self.columns.min(len);
+        let rows = len.div_ceil(columns);
+        let (row, col) = (self.focused / columns, self.focused % columns);
+        let row_len = columns.min(len - row * columns);
+
+        let next = match direction {
+            FocusDirection::Left => row * columns + (col + row_len - 1) % row_len,
+            FocusDirection::Right => row * columns + (col + 1) % row_len,
+            FocusDirection::Up => (((row + rows - 1) % rows) * columns + col).min(len - 1),
+            FocusDirection::Down => (((row + 1) % rows) * columns + col).min(len - 1),
+        };
+        Some(next)
+    }
+
+    /// Insert the [FocusScope] on `scope` and a [SelectedItem] on each of its `items`.
+    pub(crate) fn attach(
+        scope: Entity,
+        items: Vec<Entity>,
+        columns: usize,
+    ) -> impl FnOnce(&mut World) {
+        move |world: &mut World| {
+            for (i, &item) in items.iter().enumerate() {
+                world.entity_mut(item).insert(SelectedItem {
+                    selected: i == 0,
+                    scope,
+                });
+            }
+
+            world
+                .entity_mut(scope)
+                .insert(FocusScope::new(items, columns));
+        }
+    }
 }
 
 #[macro_export]
@@ -47,22 +136,65 @@ macro_rules! ui_column_scrollable {
             AccessibilityNode(NodeBuilder::new(Role::List)),
         ))
         .with_children(|builder| {
-            let mut children = [$(builder.spawn((
+            let children = vec![$(builder.spawn((
                 $x,
                 AccessibilityNode(NodeBuilder::new(Role::ListItem)),
             )).id()),+];
 
-            for i in 0..children.len() {
-                let sel = SelectedItem {
-                    selected: i == 0,
-                    next: children[(i + 1).rem_euclid(children.len())],
-                    prev: children[(i as isize - 1).rem_euclid(children.len() as isize) as usize],
-                };
+            let scope = builder.parent_entity();
+            builder.add_command(FocusScope::attach(scope, children, 1));
+        })
+    );
+}
 
-                builder.add_command(move |c: &mut World| {
-                    c.get_entity_mut(children[i]).unwrap().insert(sel);
-                });
-            }
+#[macro_export]
+macro_rules! ui_row {
+    ($parent:expr, $($x:expr),+ $(,)?) => (
+        $parent.spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    ..default()
+                },
+                ..default()
+            },
+            AccessibilityNode(NodeBuilder::new(Role::List)),
+        ))
+        .with_children(|builder| {
+            let children = vec![$(builder.spawn((
+                $x,
+                AccessibilityNode(NodeBuilder::new(Role::ListItem)),
+            )).id()),+];
+
+            let scope = builder.parent_entity();
+            let columns = children.len();
+            builder.add_command(FocusScope::attach(scope, children, columns));
+        })
+    );
+}
+
+#[macro_export]
+macro_rules! ui_grid {
+    ($parent:expr, $columns:expr, $($x:expr),+ $(,)?) => (
+        $parent.spawn((
+            NodeBundle {
+                style: Style {
+                    display: Display::Grid,
+                    grid_template_columns: RepeatedGridTrack::auto($columns as u16),
+                    ..default()
+                },
+                ..default()
+            },
+            AccessibilityNode(NodeBuilder::new(Role::Grid)),
+        ))
+        .with_children(|builder| {
+            let children = vec![$(builder.spawn((
+                $x,
+                AccessibilityNode(NodeBuilder::new(Role::Cell)),
+            )).id()),+];
+
+            let scope = builder.parent_entity();
+            builder.add_command(FocusScope::attach(scope, children, $columns));
         })
     );
 }
@@ -80,7 +212,10 @@ pub(crate) fn scroll_list(
             }
 
             let items_height = list_node.size().y;
-            let container_height = query_node.get(parent.get()).unwrap().size().y;
+            let Ok(container) = query_node.get(parent.get()) else {
+                continue;
+            };
+            let container_height = container.size().y;
 
             let max_scroll = (items_height - container_height).max(0.);
 
@@ -92,39 +227,191 @@ pub(crate) fn scroll_list(
             scrolling_list.position += dy;
             scrolling_list.position = scrolling_list.position.clamp(-max_scroll, 0.);
             style.top = Val::Px(scrolling_list.position);
-            dbg!(max_scroll, style.top, dy);
+        }
+    }
+}
+
+/// Make sure that exactly one [FocusScope] is active, `Tab` moves to the next one.
+pub(crate) fn cycle_focus_scope(
+    input: Res<ButtonInput<KeyCode>>,
+    mut scopes: Query<(Entity, &mut FocusScope)>,
+) {
+    let mut entities = scopes.iter().map(|(e, _)| e).collect::<Vec<_>>();
+    if entities.is_empty() {
+        return;
+    }
+    entities.sort();
+
+    let active = entities
+        .iter()
+        .position(|&e| scopes.get(e).is_ok_and(|(_, s)| s.active));
+
+    let next = match active {
+        None => 0,
+        Some(i) if input.just_pressed(KeyCode::Tab) => (i + 1) % entities.len(),
+        Some(_) => return,
+    };
+
+    for (entity, mut scope) in &mut scopes {
+        let active = entity == entities[next];
+        if scope.active != active {
+            scope.active = active;
         }
     }
 }
 
 pub(crate) fn change_selected(
     input: Res<ButtonInput<KeyCode>>,
-    mut selected: Query<(&mut SelectedItem)>,
+    mut scopes: Query<&mut FocusScope>,
+    mut selected: Query<&mut SelectedItem>,
 ) {
-    let go_down = if input.just_pressed(KeyCode::ArrowUp) {
-        true
-    } else if input.just_pressed(KeyCode::ArrowDown) {
-        false
-    } else {
+    let Some(direction) = FocusDirection::from_input(&input) else {
         return;
     };
 
-    let mut sel = selected.iter_mut().filter(|s| s.selected).next().unwrap();
-    sel.selected = false;
+    let Some(mut scope) = scopes.iter_mut().find(|s| s.active) else {
+        return;
+    };
+    let Some(next) = scope.neighbour(direction) else {
+        return;
+    };
+    if next == scope.focused {
+        return;
+    }
 
     // Change the currently selected item
-    let to_change = if go_down { sel.next } else { sel.prev };
-    selected.get_mut(to_change).unwrap().selected = true;
+    if let Ok(mut sel) = selected.get_mut(scope.items[scope.focused]) {
+        sel.selected = false;
+    }
+    if let Ok(mut sel) = selected.get_mut(scope.items[next]) {
+        sel.selected = true;
+    }
+    scope.focused = next;
 }
 
+/// Scroll a [ScrollingList] so that its focused item is inside the visible container.
+#[allow(clippy::type_complexity)]
+pub(crate) fn scroll_to_focused(
+    mut query_list: Query<
+        (
+            &FocusScope,
+            &mut ScrollingList,
+            &mut Style,
+            &Parent,
+            &Node,
+            &GlobalTransform,
+        ),
+        Changed<FocusScope>,
+    >,
+    query_node: Query<(&Node, &GlobalTransform)>,
+) {
+    for (scope, mut scrolling_list, mut style, parent, list_node, list_transform) in &mut query_list
+    {
+        let Some(item) = scope.focused_item() else {
+            continue;
+        };
+        let (Ok((item_node, item_transform)), Ok((container_node, _))) =
+            (query_node.get(item), query_node.get(parent.get()))
+        else {
+            continue;
+        };
+
+        let items_height = list_node.size().y;
+        let container_height = container_node.size().y;
+        let max_scroll = (items_height - container_height).max(0.);
+
+        // UI coordinates grow downward, offsets are relative to the top of the list
+        let list_top = list_transform.translation().y - 0.5 * items_height;
+        let item_top = item_transform.translation().y - 0.5 * item_node.size().y - list_top;
+        let item_bottom = item_top + item_node.size().y;
+
+        let visible_top = -scrolling_list.position;
+        let position = if item_top < visible_top {
+            -item_top
+        } else if item_bottom > visible_top + container_height {
+            container_height - item_bottom
+        } else {
+            continue;
+        };
+
+        scrolling_list.position = position.clamp(-max_scroll, 0.);
+        style.top = Val::Px(scrolling_list.position);
+    }
+}
+
+/// Highlight the focused item of every scope, dimmed if the scope is not the active one.
 pub(crate) fn change_selected_color(
-    mut query: Query<(&mut BackgroundColor, &SelectedItem), Changed<SelectedItem>>,
+    mut query: Query<(&mut BackgroundColor, Ref<SelectedItem>)>,
+    scopes: Query<Ref<FocusScope>>,
 ) {
     for (mut background, selected) in &mut query {
-        if selected.selected {
-            background.0 = Color::RED;
-        } else {
-            background.0 = Color::default();
+        let Ok(scope) = scopes.get(selected.scope) else {
+            continue;
+        };
+        if !selected.is_changed() && !scope.is_changed() {
+            continue;
         }
+
+        background.0 = match (selected.selected, scope.active) {
+            (true, true) => Color::RED,
+            (true, false) => Color::MAROON,
+            (false, _) => Color::default(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(len: u32, columns: usize, focused: usize) -> FocusScope {
+        let mut scope = FocusScope::new((0..len).map(Entity::from_raw).collect(), columns);
+        scope.focused = focused;
+        scope
+    }
+
+    #[test]
+    fn column_wraps_vertically_and_ignores_horizontal() {
+        let column = scope(3, 1, 2);
+        assert_eq!(column.neighbour(FocusDirection::Down), Some(0));
+        assert_eq!(column.neighbour(FocusDirection::Up), Some(1));
+        assert_eq!(column.neighbour(FocusDirection::Left), Some(2));
+        assert_eq!(column.neighbour(FocusDirection::Right), Some(2));
+    }
+
+    #[test]
+    fn row_wraps_horizontally_and_ignores_vertical() {
+        let row = scope(4, 4, 0);
+        assert_eq!(row.neighbour(FocusDirection::Left), Some(3));
+        assert_eq!(row.neighbour(FocusDirection::Right), Some(1));
+        assert_eq!(row.neighbour(FocusDirection::Up), Some(0));
+        assert_eq!(row.neighbour(FocusDirection::Down), Some(0));
+    }
+
+    #[test]
+    fn grid_wraps_within_a_short_last_row() {
+        // 0 1 2
+        // 3 4
+        assert_eq!(scope(5, 3, 4).neighbour(FocusDirection::Right), Some(3));
+        assert_eq!(scope(5, 3, 3).neighbour(FocusDirection::Left), Some(4));
+        assert_eq!(scope(5, 3, 4).neighbour(FocusDirection::Down), Some(1));
+        assert_eq!(scope(5, 3, 0).neighbour(FocusDirection::Up), Some(3));
+    }
+
+    #[test]
+    fn grid_clamps_to_the_last_item_in_a_short_column() {
+        assert_eq!(scope(5, 3, 2).neighbour(FocusDirection::Down), Some(4));
+        assert_eq!(scope(5, 3, 2).neighbour(FocusDirection::Up), Some(4));
+    }
+
+    #[test]
+    fn columns_wider_than_items_act_as_a_row() {
+        assert_eq!(scope(2, 5, 1).neighbour(FocusDirection::Right), Some(0));
+    }
+
+    #[test]
+    fn empty_scope_has_no_neighbour() {
+        assert_eq!(scope(0, 1, 0).neighbour(FocusDirection::Down), None);
+        assert_eq!(scope(0, 3, 0).neighbour(FocusDirection::Left), None);
     }
 }
@@ -12,8 +12,8 @@ impl UiText {
         }
     }
 
-    pub(crate) fn text_style(mut self, f: fn(&mut TextStyle) -> ()) -> Self {
-        f(&mut self.text.text.sections.first_mut().unwrap().style);
+    pub(crate) fn style(mut self, f: fn(&mut Style) -> ()) -> Self {
+        f(&mut self.text.style);
         self
     }
 
@@ -0,0 +1,223 @@
+use bevy::a11y::accesskit::{NodeBuilder, Role};
+use bevy::a11y::AccessibilityNode;
+use bevy::app::AppExit;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+
+use crate::daily::{end_daily_challenge, record_daily_score, DailyHistory, DailyRun};
+use crate::game::{AdaptiveDifficulty, FinalScore, GameRng, Mutators, ReplaySeed, RunStats};
+use crate::highscore::NameEntry;
+use crate::menu::components::column::{
+    change_selected, change_selected_color, cycle_focus_scope, FocusScope,
+};
+use crate::menu::components::text::UiText;
+use crate::meta::run_reward;
+use crate::{ui_row, AppState};
+
+/// Screen shown when a run ends in `state`, with the run statistics and the
+/// actions to continue from there.
+pub struct EndScreenPlugin {
+    pub state: AppState,
+    pub title: &'static str,
+}
+
+impl EndScreenPlugin {
+    pub fn new(state: AppState, title: &'static str) -> Self {
+        Self { state, title }
+    }
+}
+
+impl Plugin for EndScreenPlugin {
+    fn build(&self, app: &mut App) {
+        let title = self.title;
+
+        let setup = move |commands: Commands,
+                          stats: Option<Res<RunStats>>,
+                          final_score: Option<Res<FinalScore>>,
+                          adaptive: Res<AdaptiveDifficulty>,
+                          mutators: Res<Mutators>,
+                          daily: Option<Res<DailyRun>>,
+                          daily_history: Res<DailyHistory>| {
+            setup_end_screen(
+                commands,
+                title,
+                stats.as_deref(),
+                final_score.as_deref(),
+                &adaptive,
+                &mutators,
+                daily.map(|daily| daily.summary(&daily_history)),
+            )
+        };
+
+        app.add_systems(OnEnter(self.state.clone()), setup.after(record_daily_score))
+            .add_systems(OnExit(self.state.clone()), cleanup_end_screen)
+            .add_systems(
+                Update,
+                (
+                    cycle_focus_scope,
+                    change_selected.after(cycle_focus_scope),
+                    change_selected_color.after(change_selected),
+                    end_screen_key,
+                )
+                    .run_if(in_state(self.state.clone()))
+                    // The arrow keys and Enter belong to the initials prompt while it is open
+                    .run_if(not(resource_exists::<NameEntry>)),
+            );
+    }
+
+    // Added once for every end state
+    fn is_unique(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct EndScreen;
+
+#[derive(Component, Clone, Copy, Debug)]
+enum EndScreenAction {
+    /// Replay the same mode, mutators and seed
+    Retry,
+    /// Play again with a new seed, leaving the daily challenge
+    NewRun,
+    MainMenu,
+    Quit,
+}
+
+fn setup_end_screen(
+    mut commands: Commands,
+    title: &str,
+    stats: Option<&RunStats>,
+    final_score: Option<&FinalScore>,
+    adaptive: &AdaptiveDifficulty,
+    mutators: &Mutators,
+    daily: Option<Vec<String>>,
+) {
+    let style = TextStyle {
+        font_size: 60.0,
+        color: Color::rgb(0.8, 0.8, 0.8),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            EndScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            // Versus games are won by one of the players
+            let title = match final_score.and_then(|f| f.winner) {
+                Some(winner) => format!("{} wins!", winner.name()),
+                None => title.to_string(),
+            };
+            parent.spawn(TextBundle::from_section(title, style.clone()));
+
+            if let Some(stats) = stats {
+                let stats_style = TextStyle {
+                    font_size: 24.0,
+                    ..style
+                };
+                for line in stats.summary() {
+                    parent.spawn(TextBundle::from_section(line, stats_style.clone()));
+                }
+
+                if let Some(players) = final_score.and_then(|f| f.players) {
+                    parent.spawn(TextBundle::from_section(
+                        players.summary(),
+                        stats_style.clone(),
+                    ));
+                }
+
+                if let Some(final_score) = final_score {
+                    let coins = run_reward(final_score.score, stats);
+                    parent.spawn(TextBundle::from_section(
+                        format!("Coins earned: {coins}"),
+                        stats_style.clone(),
+                    ));
+                }
+
+                for line in daily.into_iter().flatten() {
+                    parent.spawn(TextBundle::from_section(line, stats_style.clone()));
+                }
+
+                for line in mutators.summary() {
+                    parent.spawn(TextBundle::from_section(line, stats_style.clone()));
+                }
+
+                for line in adaptive.summary() {
+                    parent.spawn(TextBundle::from_section(line, stats_style.clone()));
+                }
+            }
+
+            let margin = |s: &mut Style| s.margin = UiRect::axes(Val::Px(20.), Val::Px(20.));
+            ui_row![
+                parent,
+                (UiText::new("Retry").style(margin), EndScreenAction::Retry),
+                (
+                    UiText::new("New run").style(margin),
+                    EndScreenAction::NewRun
+                ),
+                (
+                    UiText::new("Main menu").style(margin),
+                    EndScreenAction::MainMenu
+                ),
+                (UiText::new("Quit").style(margin), EndScreenAction::Quit),
+            ];
+        });
+}
+
+fn end_screen_key(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    rng: Option<Res<GameRng>>,
+    mut state: ResMut<NextState<AppState>>,
+    mut exit: EventWriter<AppExit>,
+    scopes: Query<&FocusScope>,
+    actions: Query<&EndScreenAction>,
+) {
+    if !input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+
+    let Some(action) = scopes
+        .iter()
+        .find(|s| s.active)
+        .and_then(FocusScope::focused_item)
+        .and_then(|item| actions.get(item).ok())
+    else {
+        return;
+    };
+
+    match action {
+        EndScreenAction::Retry => {
+            if let Some(rng) = rng {
+                commands.insert_resource(ReplaySeed(rng.seed));
+            }
+            state.set(AppState::Game);
+        }
+        EndScreenAction::NewRun => {
+            commands.add(|world: &mut World| world.run_system_once(end_daily_challenge));
+            state.set(AppState::Game);
+        }
+        EndScreenAction::MainMenu => state.set(AppState::MainMenu),
+        EndScreenAction::Quit => {
+            exit.send(AppExit);
+        }
+    }
+}
+
+fn cleanup_end_screen(mut commands: Commands, query: Query<Entity, With<EndScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,46 +1,112 @@
 use bevy::a11y::accesskit::{NodeBuilder, Role};
 use bevy::a11y::AccessibilityNode;
 use bevy::prelude::*;
-use bevy::render::render_resource::AsBindGroupShaderType;
 
+use crate::daily::{daily_label, end_daily_challenge, DailyHistory, StartDailyChallenge};
+use crate::game::{AdaptiveDifficulty, Coop, Difficulty, ExpDrops, GameMode};
 use crate::menu::components::column::{
-    change_selected, change_selected_color, scroll_list, ScrollingList, SelectedItem,
+    change_selected, change_selected_color, cycle_focus_scope, scroll_list, scroll_to_focused,
+    FocusScope, ScrollingList,
 };
 use crate::menu::components::spacer::Spacer;
 use crate::menu::components::text::UiText;
 use crate::{ui_column_scrollable, AppState};
 
 mod components;
+mod end_screen;
+mod mutators;
+mod skill_tree;
+mod upgrades;
+
+pub use end_screen::EndScreenPlugin;
+pub use mutators::MutatorsPlugin;
+pub use skill_tree::SkillTreePlugin;
+pub use upgrades::UpgradesPlugin;
 
 #[derive(Resource)]
 pub struct MenuData {
     button_entity: Entity,
 }
 
-const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
-const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
-const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+/// What happens when `Enter` is pressed on a focused menu item
+#[derive(Component, Clone, Copy, Debug)]
+enum MenuAction {
+    StartGame,
+    Daily,
+    HighScores,
+    Upgrades,
+    Mode,
+    Coop,
+    Mutators,
+    Difficulty,
+    Adaptive,
+    ExpOrbs,
+    Settings,
+}
 
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::MainMenu), setup_menu)
-            .add_systems(
-                Update,
-                (
-                    menu_key,
-                    scroll_list,
-                    change_selected,
-                    change_selected_color,
-                )
-                    .run_if(in_state(AppState::MainMenu)),
+        app.add_systems(
+            OnEnter(AppState::MainMenu),
+            setup_menu.after(end_daily_challenge),
+        )
+        .add_systems(
+            Update,
+            (
+                menu_key,
+                scroll_list,
+                cycle_focus_scope,
+                change_selected.after(cycle_focus_scope),
+                scroll_to_focused.after(change_selected),
+                change_selected_color.after(change_selected),
             )
-            .add_systems(OnExit(AppState::MainMenu), cleanup_menu);
+                .run_if(in_state(AppState::MainMenu)),
+        )
+        .add_systems(OnExit(AppState::MainMenu), cleanup_menu);
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+fn exp_orbs_label(exp_drops: &ExpDrops) -> String {
+    format!("Exp orbs: {}.", on_off(exp_drops.orbs))
+}
+
+fn coop_label(coop: &Coop) -> &'static str {
+    if coop.enabled {
+        "Players: 2 (co-op)."
+    } else {
+        "Players: 1."
     }
 }
 
-fn setup_menu(mut commands: Commands) {
+fn adaptive_label(adaptive: &AdaptiveDifficulty) -> String {
+    format!("Adaptive difficulty: {}.", on_off(adaptive.enabled))
+}
+
+fn setup_menu(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    coop: Res<Coop>,
+    difficulty: Res<Difficulty>,
+    adaptive: Res<AdaptiveDifficulty>,
+    exp_drops: Res<ExpDrops>,
+    daily_history: Res<DailyHistory>,
+) {
+    let daily_label = daily_label(&daily_history);
+    let mode_label = format!("Mode: {}.", mode.name());
+    let difficulty_label = format!("Difficulty: {}.", difficulty.name());
+    let adaptive_label = adaptive_label(&adaptive);
+    let exp_orbs_label = exp_orbs_label(&exp_drops);
+
     let button_entity = commands
         .spawn(NodeBundle {
             style: Style {
@@ -61,8 +127,17 @@ fn setup_menu(mut commands: Commands) {
                 .with_children(|builder| {
                     ui_column_scrollable![
                         builder,
-                        UiText::new("Start new game!"),
-                        UiText::new("Settings."),
+                        (UiText::new("Start new game!"), MenuAction::StartGame),
+                        (UiText::new(&daily_label), MenuAction::Daily),
+                        (UiText::new("High scores."), MenuAction::HighScores),
+                        (UiText::new("Upgrades."), MenuAction::Upgrades),
+                        (UiText::new(&mode_label), MenuAction::Mode),
+                        (UiText::new(coop_label(&coop)), MenuAction::Coop),
+                        (UiText::new("Mutators."), MenuAction::Mutators),
+                        (UiText::new(&difficulty_label), MenuAction::Difficulty),
+                        (UiText::new(&adaptive_label), MenuAction::Adaptive),
+                        (UiText::new(&exp_orbs_label), MenuAction::ExpOrbs),
+                        (UiText::new("Settings."), MenuAction::Settings),
                     ];
                 });
         })
@@ -70,9 +145,61 @@ fn setup_menu(mut commands: Commands) {
     commands.insert_resource(MenuData { button_entity });
 }
 
-fn menu_key(mut next_state: ResMut<NextState<AppState>>, input: Res<ButtonInput<KeyCode>>) {
-    if input.pressed(KeyCode::Enter) {
-        next_state.set(AppState::Game)
+#[allow(clippy::too_many_arguments)]
+fn menu_key(
+    mut next_state: ResMut<NextState<AppState>>,
+    input: Res<ButtonInput<KeyCode>>,
+    mut mode: ResMut<GameMode>,
+    mut coop: ResMut<Coop>,
+    mut difficulty: ResMut<Difficulty>,
+    mut adaptive: ResMut<AdaptiveDifficulty>,
+    mut exp_drops: ResMut<ExpDrops>,
+    mut start_daily: EventWriter<StartDailyChallenge>,
+    scopes: Query<&FocusScope>,
+    mut actions: Query<(&MenuAction, &mut Text)>,
+) {
+    if !input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+
+    let Some((action, mut text)) = scopes
+        .iter()
+        .find(|s| s.active)
+        .and_then(FocusScope::focused_item)
+        .and_then(|item| actions.get_mut(item).ok())
+    else {
+        return;
+    };
+
+    match action {
+        MenuAction::StartGame => next_state.set(AppState::Game),
+        MenuAction::Daily => {
+            start_daily.send(StartDailyChallenge);
+        }
+        MenuAction::HighScores => next_state.set(AppState::HighScores),
+        MenuAction::Upgrades => next_state.set(AppState::Upgrades),
+        MenuAction::Coop => {
+            coop.enabled = !coop.enabled;
+            text.sections[0].value = coop_label(&coop).to_string();
+        }
+        MenuAction::Mutators => next_state.set(AppState::Mutators),
+        MenuAction::Mode => {
+            *mode = mode.next();
+            text.sections[0].value = format!("Mode: {}.", mode.name());
+        }
+        MenuAction::Difficulty => {
+            *difficulty = difficulty.next();
+            text.sections[0].value = format!("Difficulty: {}.", difficulty.name());
+        }
+        MenuAction::Adaptive => {
+            adaptive.enabled = !adaptive.enabled;
+            text.sections[0].value = adaptive_label(&adaptive);
+        }
+        MenuAction::ExpOrbs => {
+            exp_drops.orbs = !exp_drops.orbs;
+            text.sections[0].value = exp_orbs_label(&exp_drops);
+        }
+        MenuAction::Settings => {}
     }
 }
 
@@ -0,0 +1,175 @@
+use bevy::a11y::accesskit::{NodeBuilder, Role};
+use bevy::a11y::AccessibilityNode;
+use bevy::prelude::*;
+
+use crate::game::{Mutator, Mutators};
+use crate::menu::components::column::{
+    change_selected, change_selected_color, cycle_focus_scope, scroll_list, scroll_to_focused,
+    FocusScope, ScrollingList,
+};
+use crate::menu::components::spacer::Spacer;
+use crate::menu::components::text::UiText;
+use crate::menu::on_off;
+use crate::{ui_column_scrollable, AppState};
+
+/// Screen where the [Mutator]s of the next runs are toggled
+pub struct MutatorsPlugin;
+
+impl Plugin for MutatorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Mutators), setup_mutators)
+            .add_systems(OnExit(AppState::Mutators), cleanup_mutators)
+            .add_systems(
+                Update,
+                (
+                    mutators_key,
+                    scroll_list,
+                    cycle_focus_scope,
+                    change_selected.after(cycle_focus_scope),
+                    scroll_to_focused.after(change_selected),
+                    change_selected_color.after(change_selected),
+                    update_mutator_labels.after(mutators_key),
+                )
+                    .run_if(in_state(AppState::Mutators)),
+            );
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct MutatorsScreen;
+
+#[derive(Component, Clone, Copy, Debug)]
+enum MutatorsAction {
+    Toggle(Mutator),
+    Back,
+}
+
+#[derive(Component, Clone, Copy)]
+struct MultiplierText;
+
+fn mutator_label(mutators: &Mutators, mutator: Mutator) -> String {
+    format!(
+        "{}: {} - {} (x{:.2})",
+        mutator.name(),
+        on_off(mutators.contains(&mutator)),
+        mutator.description(),
+        mutator.score_multiplier()
+    )
+}
+
+fn multiplier_label(mutators: &Mutators) -> String {
+    format!("Score multiplier: x{:.2}", mutators.score_multiplier())
+}
+
+fn setup_mutators(mut commands: Commands, mutators: Res<Mutators>) {
+    let labels = Mutator::ALL.map(|mutator| mutator_label(&mutators, mutator));
+
+    commands
+        .spawn((
+            MutatorsScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Start,
+                    align_items: AlignItems::Start,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                MultiplierText,
+                UiText::new(&multiplier_label(&mutators))
+                    .style(|s| s.margin = UiRect::all(Val::Px(20.))),
+            ));
+
+            builder
+                .spawn(Spacer::around(Val::Px(20.)).style(|s| {
+                    s.overflow = Overflow::clip_y();
+                }))
+                .with_children(|builder| {
+                    ui_column_scrollable![
+                        builder,
+                        (
+                            UiText::new(&labels[0]),
+                            MutatorsAction::Toggle(Mutator::ALL[0])
+                        ),
+                        (
+                            UiText::new(&labels[1]),
+                            MutatorsAction::Toggle(Mutator::ALL[1])
+                        ),
+                        (
+                            UiText::new(&labels[2]),
+                            MutatorsAction::Toggle(Mutator::ALL[2])
+                        ),
+                        (
+                            UiText::new(&labels[3]),
+                            MutatorsAction::Toggle(Mutator::ALL[3])
+                        ),
+                        (
+                            UiText::new(&labels[4]),
+                            MutatorsAction::Toggle(Mutator::ALL[4])
+                        ),
+                        (
+                            UiText::new(&labels[5]),
+                            MutatorsAction::Toggle(Mutator::ALL[5])
+                        ),
+                        (UiText::new("Back."), MutatorsAction::Back),
+                    ];
+                });
+        });
+}
+
+fn mutators_key(
+    input: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<NextState<AppState>>,
+    mut mutators: ResMut<Mutators>,
+    scopes: Query<&FocusScope>,
+    actions: Query<&MutatorsAction>,
+) {
+    if !input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+
+    let Some(action) = scopes
+        .iter()
+        .find(|s| s.active)
+        .and_then(FocusScope::focused_item)
+        .and_then(|item| actions.get(item).ok())
+    else {
+        return;
+    };
+
+    match action {
+        MutatorsAction::Toggle(mutator) => mutators.toggle(*mutator),
+        MutatorsAction::Back => state.set(AppState::MainMenu),
+    }
+}
+
+fn update_mutator_labels(
+    mutators: Res<Mutators>,
+    mut items: Query<(&mut Text, &MutatorsAction)>,
+    mut multiplier: Query<&mut Text, (With<MultiplierText>, Without<MutatorsAction>)>,
+) {
+    if !mutators.is_changed() {
+        return;
+    }
+
+    for (mut text, action) in &mut items {
+        if let MutatorsAction::Toggle(mutator) = action {
+            text.sections[0].value = mutator_label(&mutators, *mutator);
+        }
+    }
+    for mut text in &mut multiplier {
+        text.sections[0].value = multiplier_label(&mutators);
+    }
+}
+
+fn cleanup_mutators(mut commands: Commands, query: Query<Entity, With<MutatorsScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
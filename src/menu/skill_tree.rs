@@ -0,0 +1,170 @@
+use bevy::a11y::accesskit::{NodeBuilder, Role};
+use bevy::a11y::AccessibilityNode;
+use bevy::prelude::*;
+
+use crate::game::{GameState, Skill, SkillBranch, SkillTree};
+use crate::menu::components::column::{
+    change_selected, change_selected_color, cycle_focus_scope, FocusScope,
+};
+use crate::menu::components::text::UiText;
+use crate::ui_grid;
+
+/// Screen shown on level up, where skill points are spent
+pub struct SkillTreePlugin;
+
+impl Plugin for SkillTreePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::LevelingUp), setup_skill_tree)
+            .add_systems(OnExit(GameState::LevelingUp), cleanup_skill_tree)
+            .add_systems(
+                Update,
+                (
+                    skill_tree_key,
+                    cycle_focus_scope,
+                    change_selected.after(cycle_focus_scope),
+                    change_selected_color.after(change_selected),
+                    update_skill_labels.after(skill_tree_key),
+                )
+                    .run_if(in_state(GameState::LevelingUp)),
+            );
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct SkillTreeScreen;
+
+#[derive(Component, Clone, Copy)]
+struct SkillItem(Skill);
+
+#[derive(Component, Clone, Copy)]
+struct SkillPointsText;
+
+const COLUMN_WIDTH: Val = Val::Px(260.);
+
+fn skill_label(tree: &SkillTree, skill: Skill) -> String {
+    let locked = skill
+        .requires()
+        .filter(|&s| tree.rank(s) == 0)
+        .map_or(String::new(), |s| format!(" (needs {})", s.name()));
+    let taken =
+        if skill.damage_type().is_some() && tree.rank(skill) == 0 && tree.affinity().is_some() {
+            " (affinity taken)"
+        } else {
+            ""
+        };
+
+    format!(
+        "{} {}/{}{locked}{taken}",
+        skill.name(),
+        tree.rank(skill),
+        skill.max_rank()
+    )
+}
+
+fn skill_item(tree: &SkillTree, index: usize) -> (UiText, SkillItem) {
+    let skill = Skill::ALL[index];
+    let text = UiText::new(&skill_label(tree, skill)).style(|s| s.width = COLUMN_WIDTH);
+
+    (text, SkillItem(skill))
+}
+
+fn setup_skill_tree(mut commands: Commands, tree: Res<SkillTree>) {
+    commands
+        .spawn((
+            SkillTreeScreen,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(20.),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                SkillPointsText,
+                UiText::new(&format!("Level up! Skill points: {}", tree.points)),
+            ));
+
+            builder
+                .spawn(NodeBundle::default())
+                .with_children(|builder| {
+                    for branch in SkillBranch::ALL {
+                        builder.spawn(UiText::new(branch.name()).style(|s| s.width = COLUMN_WIDTH));
+                    }
+                });
+
+            ui_grid![
+                builder,
+                SkillBranch::ALL.len(),
+                skill_item(&tree, 0),
+                skill_item(&tree, 1),
+                skill_item(&tree, 2),
+                skill_item(&tree, 3),
+                skill_item(&tree, 4),
+                skill_item(&tree, 5),
+                skill_item(&tree, 6),
+                skill_item(&tree, 7),
+                skill_item(&tree, 8),
+                skill_item(&tree, 9),
+                skill_item(&tree, 10),
+                skill_item(&tree, 11),
+            ];
+        });
+}
+
+fn skill_tree_key(
+    input: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<NextState<GameState>>,
+    mut tree: ResMut<SkillTree>,
+    scopes: Query<&FocusScope>,
+    items: Query<&SkillItem>,
+) {
+    if !input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+
+    let Some(SkillItem(skill)) = scopes
+        .iter()
+        .find(|s| s.active)
+        .and_then(FocusScope::focused_item)
+        .and_then(|item| items.get(item).ok())
+    else {
+        return;
+    };
+
+    if tree.learn(*skill) && !tree.has_choices() {
+        state.set(GameState::Looping);
+    }
+}
+
+fn update_skill_labels(
+    tree: Res<SkillTree>,
+    mut items: Query<(&mut Text, &SkillItem)>,
+    mut points: Query<&mut Text, (With<SkillPointsText>, Without<SkillItem>)>,
+) {
+    if !tree.is_changed() {
+        return;
+    }
+
+    for (mut text, SkillItem(skill)) in &mut items {
+        text.sections[0].value = skill_label(&tree, *skill);
+    }
+    for mut text in &mut points {
+        text.sections[0].value = format!("Level up! Skill points: {}", tree.points);
+    }
+}
+
+fn cleanup_skill_tree(mut commands: Commands, query: Query<Entity, With<SkillTreeScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
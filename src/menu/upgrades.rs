@@ -0,0 +1,176 @@
+use bevy::a11y::accesskit::{NodeBuilder, Role};
+use bevy::a11y::AccessibilityNode;
+use bevy::prelude::*;
+
+use crate::menu::components::column::{
+    change_selected, change_selected_color, cycle_focus_scope, scroll_list, scroll_to_focused,
+    FocusScope, ScrollingList,
+};
+use crate::menu::components::spacer::Spacer;
+use crate::menu::components::text::UiText;
+use crate::meta::{MetaSave, Upgrade};
+use crate::{ui_column_scrollable, AppState};
+
+/// Shop where the meta currency buys permanent [Upgrade]s
+pub struct UpgradesPlugin;
+
+impl Plugin for UpgradesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Upgrades), setup_upgrades)
+            .add_systems(OnExit(AppState::Upgrades), cleanup_upgrades)
+            .add_systems(
+                Update,
+                (
+                    upgrades_key,
+                    scroll_list,
+                    cycle_focus_scope,
+                    change_selected.after(cycle_focus_scope),
+                    scroll_to_focused.after(change_selected),
+                    change_selected_color.after(change_selected),
+                    update_upgrade_labels.after(upgrades_key),
+                )
+                    .run_if(in_state(AppState::Upgrades)),
+            );
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+struct UpgradesScreen;
+
+#[derive(Component, Clone, Copy, Debug)]
+enum UpgradesAction {
+    Buy(Upgrade),
+    Back,
+}
+
+#[derive(Component, Clone, Copy)]
+struct CurrencyText;
+
+fn upgrade_label(meta: &MetaSave, upgrade: Upgrade) -> String {
+    let level = meta.level(upgrade);
+    let max = upgrade.max_level();
+    if level >= max {
+        return format!("{} {level}/{max} - maxed", upgrade.name());
+    }
+
+    format!(
+        "{} {level}/{max} - {} coins",
+        upgrade.name(),
+        upgrade.cost(level)
+    )
+}
+
+fn setup_upgrades(mut commands: Commands, meta: Res<MetaSave>) {
+    let labels = Upgrade::ALL.map(|upgrade| upgrade_label(&meta, upgrade));
+
+    commands
+        .spawn((
+            UpgradesScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Start,
+                    align_items: AlignItems::Start,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                CurrencyText,
+                UiText::new(&format!("Coins: {}", meta.currency))
+                    .style(|s| s.margin = UiRect::all(Val::Px(20.))),
+            ));
+
+            builder
+                .spawn(Spacer::around(Val::Px(20.)).style(|s| {
+                    s.overflow = Overflow::clip_y();
+                }))
+                .with_children(|builder| {
+                    ui_column_scrollable![
+                        builder,
+                        (
+                            UiText::new(&labels[0]),
+                            UpgradesAction::Buy(Upgrade::ALL[0])
+                        ),
+                        (
+                            UiText::new(&labels[1]),
+                            UpgradesAction::Buy(Upgrade::ALL[1])
+                        ),
+                        (
+                            UiText::new(&labels[2]),
+                            UpgradesAction::Buy(Upgrade::ALL[2])
+                        ),
+                        (
+                            UiText::new(&labels[3]),
+                            UpgradesAction::Buy(Upgrade::ALL[3])
+                        ),
+                        (
+                            UiText::new(&labels[4]),
+                            UpgradesAction::Buy(Upgrade::ALL[4])
+                        ),
+                        (UiText::new("Back."), UpgradesAction::Back),
+                    ];
+                });
+        });
+}
+
+fn upgrades_key(
+    input: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<NextState<AppState>>,
+    mut meta: ResMut<MetaSave>,
+    scopes: Query<&FocusScope>,
+    actions: Query<&UpgradesAction>,
+) {
+    if !input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+
+    let Some(action) = scopes
+        .iter()
+        .find(|s| s.active)
+        .and_then(FocusScope::focused_item)
+        .and_then(|item| actions.get(item).ok())
+    else {
+        return;
+    };
+
+    match action {
+        UpgradesAction::Buy(upgrade) => {
+            if meta.buy(*upgrade) {
+                if let Err(err) = meta.save() {
+                    warn!("Could not save {}: {err}", MetaSave::PATH);
+                }
+            }
+        }
+        UpgradesAction::Back => state.set(AppState::MainMenu),
+    }
+}
+
+fn update_upgrade_labels(
+    meta: Res<MetaSave>,
+    mut items: Query<(&mut Text, &UpgradesAction)>,
+    mut currency: Query<&mut Text, (With<CurrencyText>, Without<UpgradesAction>)>,
+) {
+    if !meta.is_changed() {
+        return;
+    }
+
+    for (mut text, action) in &mut items {
+        if let UpgradesAction::Buy(upgrade) = action {
+            text.sections[0].value = upgrade_label(&meta, *upgrade);
+        }
+    }
+    for mut text in &mut currency {
+        text.sections[0].value = format!("Coins: {}", meta.currency);
+    }
+}
+
+fn cleanup_upgrades(mut commands: Commands, query: Query<Entity, With<UpgradesScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
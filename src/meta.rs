@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::daily::DailyRun;
+use crate::game::{FinalScore, RunStats, StartingBonuses};
+use crate::AppState;
+
+pub struct MetaPlugin;
+
+impl Plugin for MetaPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_meta)
+            .add_systems(OnEnter(AppState::GameOver), award_currency)
+            .add_systems(OnEnter(AppState::GameWon), award_currency)
+            .add_systems(Update, update_starting_bonuses);
+    }
+}
+
+/// Permanent bonuses bought with the currency earned across runs
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Upgrade {
+    StartingAttack,
+    ExtraLife,
+    PaddleSize,
+    HeadStart,
+    Magnet,
+}
+
+impl Upgrade {
+    pub const ALL: [Upgrade; 5] = [
+        Upgrade::StartingAttack,
+        Upgrade::ExtraLife,
+        Upgrade::PaddleSize,
+        Upgrade::HeadStart,
+        Upgrade::Magnet,
+    ];
+
+    /// Paddle width gained by every [Upgrade::PaddleSize] level
+    pub const PADDLE_WIDTH: f32 = 15.0;
+    /// Exp gained by every [Upgrade::HeadStart] level
+    pub const HEAD_START_EXP: u32 = 25;
+    /// Exp orb magnet radius gained by every [Upgrade::Magnet] level
+    pub const MAGNET_RADIUS: f32 = 60.0;
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Upgrade::StartingAttack => "Starting attack",
+            Upgrade::ExtraLife => "Extra life",
+            Upgrade::PaddleSize => "Bigger paddle",
+            Upgrade::HeadStart => "Head start exp",
+            Upgrade::Magnet => "Exp magnet",
+        }
+    }
+
+    pub fn max_level(self) -> u32 {
+        match self {
+            Upgrade::StartingAttack => 5,
+            Upgrade::ExtraLife => 3,
+            Upgrade::PaddleSize => 4,
+            Upgrade::HeadStart => 5,
+            Upgrade::Magnet => 3,
+        }
+    }
+
+    /// Cost of buying the level after `level`
+    pub fn cost(self, level: u32) -> u32 {
+        let base = match self {
+            Upgrade::StartingAttack => 40,
+            Upgrade::ExtraLife => 100,
+            Upgrade::PaddleSize => 30,
+            Upgrade::HeadStart => 20,
+            Upgrade::Magnet => 50,
+        };
+        base * (level + 1)
+    }
+}
+
+/// Meta progression saved in [MetaSave::PATH]. Bump [MetaSave::VERSION] and add
+/// a step to [MetaSave::migrate] whenever the format changes.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone)]
+pub struct MetaSave {
+    pub version: u32,
+    pub currency: u32,
+    pub upgrades: BTreeMap<Upgrade, u32>,
+}
+
+impl Default for MetaSave {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            currency: 0,
+            upgrades: BTreeMap::new(),
+        }
+    }
+}
+
+impl MetaSave {
+    pub const PATH: &'static str = "meta_progress.json";
+    pub const VERSION: u32 = 1;
+
+    pub fn load() -> io::Result<Self> {
+        let file = match fs::read_to_string(Self::PATH) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err),
+        };
+
+        let value = serde_json::from_str(&file)?;
+        Self::migrate(value).map_err(io::Error::from)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let file = serde_json::to_string_pretty(self)?;
+        fs::write(Self::PATH, file)
+    }
+
+    /// Read a save of any known version
+    fn migrate(value: Value) -> serde_json::Result<Self> {
+        let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+
+        match version {
+            1 => serde_json::from_value(value),
+            v => Err(serde::de::Error::custom(format!(
+                "unsupported save version {v}"
+            ))),
+        }
+    }
+
+    pub fn level(&self, upgrade: Upgrade) -> u32 {
+        self.upgrades.get(&upgrade).copied().unwrap_or(0)
+    }
+
+    /// Buy the next level of `upgrade`, returns `false` if it is maxed out or too expensive
+    pub fn buy(&mut self, upgrade: Upgrade) -> bool {
+        let level = self.level(upgrade);
+        let cost = upgrade.cost(level);
+        if level >= upgrade.max_level() || self.currency < cost {
+            return false;
+        }
+
+        self.currency -= cost;
+        self.upgrades.insert(upgrade, level + 1);
+        true
+    }
+
+    pub fn starting_bonuses(&self) -> StartingBonuses {
+        StartingBonuses {
+            attack: self.level(Upgrade::StartingAttack),
+            lives: self.level(Upgrade::ExtraLife),
+            paddle_width: self.level(Upgrade::PaddleSize) as f32 * Upgrade::PADDLE_WIDTH,
+            exp: self.level(Upgrade::HeadStart) * Upgrade::HEAD_START_EXP,
+            magnet_radius: self.level(Upgrade::Magnet) as f32 * Upgrade::MAGNET_RADIUS,
+        }
+    }
+}
+
+/// Currency earned by a finished run
+pub fn run_reward(score: u32, stats: &RunStats) -> u32 {
+    let blocks: u32 = stats.blocks_destroyed.values().sum();
+    score / 100 + stats.max_level * 5 + blocks / 10
+}
+
+fn load_meta(mut commands: Commands) {
+    let meta = MetaSave::load().unwrap_or_else(|err| {
+        warn!("Could not load {}: {err}", MetaSave::PATH);
+        MetaSave::default()
+    });
+
+    commands.insert_resource(meta);
+}
+
+fn award_currency(
+    mut meta: ResMut<MetaSave>,
+    final_score: Option<Res<FinalScore>>,
+    stats: Option<Res<RunStats>>,
+) {
+    let (Some(final_score), Some(stats)) = (final_score, stats) else {
+        return;
+    };
+
+    meta.currency += run_reward(final_score.score, &stats);
+    if let Err(err) = meta.save() {
+        warn!("Could not save {}: {err}", MetaSave::PATH);
+    }
+}
+
+/// The bonuses stay off during the daily challenge, they are given back when it ends
+fn update_starting_bonuses(
+    meta: Res<MetaSave>,
+    daily: Option<Res<DailyRun>>,
+    mut bonuses: ResMut<StartingBonuses>,
+) {
+    if meta.is_changed() && daily.is_none() {
+        *bonuses = meta.starting_bonuses();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn migrate_reads_the_current_version() {
+        let save = MetaSave::migrate(json!({
+            "version": 1,
+            "currency": 42,
+            "upgrades": { "ExtraLife": 2 },
+        }))
+        .unwrap();
+
+        assert_eq!(save.currency, 42);
+        assert_eq!(save.level(Upgrade::ExtraLife), 2);
+        assert_eq!(save.level(Upgrade::Magnet), 0);
+    }
+
+    #[test]
+    fn migrate_rejects_unknown_versions() {
+        let current = json!({ "currency": 0, "upgrades": {} });
+        assert!(MetaSave::migrate(current.clone()).is_err());
+
+        let mut future = current;
+        future["version"] = json!(MetaSave::VERSION + 1);
+        assert!(MetaSave::migrate(future).is_err());
+    }
+
+    #[test]
+    fn migrate_round_trips_a_save() {
+        let mut save = MetaSave {
+            currency: 500,
+            ..default()
+        };
+        assert!(save.buy(Upgrade::PaddleSize));
+
+        let value = serde_json::to_value(&save).unwrap();
+        let loaded = MetaSave::migrate(value).unwrap();
+        assert_eq!(loaded.currency, save.currency);
+        assert_eq!(loaded.upgrades, save.upgrades);
+    }
+
+    #[test]
+    fn buy_spends_the_cost_of_the_next_level() {
+        let mut save = MetaSave {
+            currency: Upgrade::HeadStart.cost(0) + Upgrade::HeadStart.cost(1),
+            ..default()
+        };
+
+        assert!(save.buy(Upgrade::HeadStart));
+        assert_eq!(save.currency, Upgrade::HeadStart.cost(1));
+        assert!(save.buy(Upgrade::HeadStart));
+        assert_eq!(save.currency, 0);
+        assert_eq!(save.level(Upgrade::HeadStart), 2);
+    }
+
+    #[test]
+    fn buy_fails_without_enough_currency() {
+        let mut save = MetaSave {
+            currency: Upgrade::ExtraLife.cost(0) - 1,
+            ..default()
+        };
+
+        assert!(!save.buy(Upgrade::ExtraLife));
+        assert_eq!(save.currency, Upgrade::ExtraLife.cost(0) - 1);
+        assert_eq!(save.level(Upgrade::ExtraLife), 0);
+    }
+
+    #[test]
+    fn buy_stops_at_max_level() {
+        let upgrade = Upgrade::ExtraLife;
+        let mut save = MetaSave {
+            currency: u32::MAX,
+            ..default()
+        };
+        for _ in 0..upgrade.max_level() {
+            assert!(save.buy(upgrade));
+        }
+
+        let currency = save.currency;
+        assert!(!save.buy(upgrade));
+        assert_eq!(save.currency, currency);
+        assert_eq!(save.level(upgrade), upgrade.max_level());
+    }
+}